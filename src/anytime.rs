@@ -0,0 +1,39 @@
+use crate::ls::LocalSearch;
+use crate::parser;
+use std::io::Write;
+
+impl LocalSearch {
+    // called each time best_cost improves
+    pub(crate) fn improved(&self, elapsed: f64, iterations: u64) {
+        if self.anytime_output.is_none() && !self.anytime_events {
+            return;
+        }
+        let solution = self.best_solution();
+
+        if let Some(filename) = &self.anytime_output {
+            let _ = parser::save_solution_atomic(filename, &solution, self.anytime_format.as_deref());
+        }
+        if self.anytime_events {
+            let event = serde_json::json!({
+                "event": "best",
+                "cost": solution.cost,
+                "unassigned": solution.unassigned.len(),
+                "time": elapsed,
+                "iterations": iterations,
+            });
+            // a consumer that stopped reading is no reason to stop the search
+            let mut stdout = std::io::stdout();
+            let _ = writeln!(stdout, "{}", event);
+            let _ = stdout.flush();
+        }
+    }
+
+    // with --events stdout carries nothing but the events, every other line goes to stderr
+    pub(crate) fn log(&self, line: impl std::fmt::Display) {
+        if self.anytime_events {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::config::Config;
+use crate::ls::LocalSearch;
+use crate::parser;
+use crate::validator;
+use std::{fs, fs::File, io::Write, path::Path};
+
+pub struct BenchRun {
+    pub instance: String,
+    pub constructor: String,
+    pub time: i32,
+    pub seed: u64,
+    pub construction_cost: i32,
+    pub cost: i32,
+    pub time_to_best: f64,
+    pub iterations: u64,
+    pub valid: Result<i32, String>,
+}
+
+pub struct BenchSummary {
+    pub instance: String,
+    pub constructor: String,
+    pub time: i32,
+    pub mean: f64,
+    pub median: f64,
+    pub min: i32,
+    pub stddev: f64,
+}
+
+pub fn run_instance(filepath: &str, time: i32, seed: u64, config: &Config) -> BenchRun {
+    let (res, zone, veh) = parser::read_file(filepath);
+
+    let mut ls = LocalSearch::new(res, zone, veh);
+    ls.set_config(config.clone());
+    ls.solve(time, seed);
+
+    let solution = ls.best_solution();
+    BenchRun {
+        instance: filepath.to_string(),
+        constructor: config.constructor.clone(),
+        time,
+        seed,
+        construction_cost: ls.construction_cost,
+        cost: ls.best_cost,
+        time_to_best: ls.time_to_best,
+        iterations: ls.iterations,
+        valid: validator::validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution),
+    }
+}
+
+pub fn instances(dir: &str) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .expect("could not read directory")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    files
+}
+
+pub fn summarise(runs: &[BenchRun]) -> Vec<BenchSummary> {
+    let mut summaries: Vec<BenchSummary> = vec![];
+
+    let mut keys: Vec<(&str, &str, i32)> = vec![];
+    for run in runs {
+        let key = (run.instance.as_str(), run.constructor.as_str(), run.time);
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    for (instance, constructor, time) in keys {
+        let mut costs: Vec<i32> = runs
+            .iter()
+            .filter(|run| run.instance == instance && run.constructor == constructor && run.time == time)
+            .map(|run| run.cost)
+            .collect();
+        costs.sort();
+
+        let n = costs.len() as f64;
+        let mean = costs.iter().sum::<i32>() as f64 / n;
+        let median = if costs.len().is_multiple_of(2) {
+            (costs[costs.len() / 2 - 1] + costs[costs.len() / 2]) as f64 / 2.0
+        } else {
+            costs[costs.len() / 2] as f64
+        };
+        let variance = costs.iter().map(|c| (*c as f64 - mean).powi(2)).sum::<f64>() / n;
+
+        summaries.push(BenchSummary {
+            instance: instance.to_string(),
+            constructor: constructor.to_string(),
+            time,
+            mean,
+            median,
+            min: costs[0],
+            stddev: variance.sqrt(),
+        });
+    }
+    summaries
+}
+
+fn summary_filename(filename: &str) -> String {
+    let path = Path::new(filename);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}_summary.csv", stem))
+        .to_string_lossy()
+        .to_string()
+}
+
+pub fn write_runs(runs: &[BenchRun], filename: &str) -> std::io::Result<()> {
+    let mut file = File::create(filename)?;
+
+    file.write_all(b"instance;constructor;time;seed;construction_cost;cost;time_to_best;iterations;valid\n")?;
+    for run in runs {
+        let valid = match &run.valid {
+            Ok(_) => "ok".to_string(),
+            Err(e) => e.clone(),
+        };
+        file.write_all(
+            format!(
+                "{};{};{};{};{};{};{:.3};{};{}\n",
+                run.instance,
+                run.constructor,
+                run.time,
+                run.seed,
+                run.construction_cost,
+                run.cost,
+                run.time_to_best,
+                run.iterations,
+                valid
+            )
+            .as_bytes(),
+        )?;
+    }
+    Ok(())
+}
+
+pub fn write_summary(summaries: &[BenchSummary], filename: &str) -> std::io::Result<()> {
+    let mut file = File::create(filename)?;
+
+    file.write_all(b"instance;constructor;time;mean;median;min;stddev\n")?;
+    for s in summaries {
+        file.write_all(
+            format!(
+                "{};{};{};{:.2};{:.1};{};{:.2}\n",
+                s.instance, s.constructor, s.time, s.mean, s.median, s.min, s.stddev
+            )
+            .as_bytes(),
+        )?;
+    }
+    Ok(())
+}
+
+// one config per constructor to compare
+pub fn bench(dir: &str, output: &str, times: &[i32], seeds: &[u64], configs: &[Config]) -> std::io::Result<()> {
+    let mut runs: Vec<BenchRun> = vec![];
+
+    for instance in instances(dir) {
+        for config in configs {
+            for time in times {
+                for seed in seeds {
+                    let run = run_instance(&instance, *time, *seed, config);
+                    if let Err(e) = &run.valid {
+                        println!("{} ({}, seed {}) is not valid: {}", instance, config.constructor, seed, e);
+                    }
+                    runs.push(run);
+                }
+            }
+        }
+    }
+
+    let summaries = summarise(&runs);
+    for s in &summaries {
+        println!(
+            "{} {} t={}: mean {:.2} median {:.1} min {} stddev {:.2}",
+            s.instance, s.constructor, s.time, s.mean, s.median, s.min, s.stddev
+        );
+    }
+
+    write_runs(&runs, output)?;
+    write_summary(&summaries, &summary_filename(output))
+}
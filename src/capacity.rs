@@ -0,0 +1,183 @@
+use crate::ls::LocalSearch;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapacityMode {
+    Hard,      // a layout over capacity is infeasible, full zones are entered by swapping cars
+    Soft(i32), // every car over capacity adds this penalty to the cost
+}
+
+impl LocalSearch {
+    pub(crate) fn cars_in_zone(&self, veh_to_zon: &[i32], zon_id: i32) -> i32 {
+        veh_to_zon.iter().filter(|zone| **zone == zon_id).count() as i32
+    }
+
+    pub(crate) fn has_room(&self, zon_id: i32) -> bool {
+        match self.zones[zon_id as usize].capacity {
+            Some(capacity) => self.cars_in_zone(&self.veh_to_zon, zon_id) < capacity,
+            None => true,
+        }
+    }
+
+    fn overflow(&self, veh_to_zon: &[i32]) -> i32 {
+        self.zones
+            .iter()
+            .filter_map(|zone| {
+                zone.capacity
+                    .map(|capacity| (self.cars_in_zone(veh_to_zon, zone.id) - capacity).max(0))
+            })
+            .sum()
+    }
+
+    pub(crate) fn capacity_penalty(&self, veh_to_zon: &[i32]) -> i32 {
+        match self.capacity_mode {
+            CapacityMode::Soft(penalty) => penalty * self.overflow(veh_to_zon),
+            CapacityMode::Hard => 0,
+        }
+    }
+
+    pub(crate) fn capacity_holds(&self) -> bool {
+        self.capacity_mode != CapacityMode::Hard || self.overflow(&self.veh_to_zon) == 0
+    }
+
+    // zone the instance or the constraints hold a car in, None when it may park anywhere
+    fn held_zone(&self, veh_id: i32) -> Option<i32> {
+        match self.constraints.fixed_zone(veh_id).or(self.vehicle[veh_id as usize].zone) {
+            Some(zone) => Some(zone),
+            None if self.constraints.pinned.iter().any(|(_, veh)| *veh == veh_id) => self.pinned_zone(veh_id),
+            None => None,
+        }
+    }
+
+    // no layout can keep a hard capacity the held cars already exceed
+    pub fn held_cars_fit(&self) -> Result<(), String> {
+        if self.capacity_mode != CapacityMode::Hard {
+            return Ok(());
+        }
+        let held: Vec<i32> = (0..self.vehicle.len() as i32).map(|veh| self.held_zone(veh).unwrap_or(-1)).collect();
+        for zone in &self.zones {
+            let cars = self.cars_in_zone(&held, zone.id);
+            if let Some(capacity) = zone.capacity.filter(|capacity| cars > *capacity) {
+                return Err(format!("z{} has room for {} cars, but {} are held there", zone.id, capacity, cars));
+            }
+        }
+        Ok(())
+    }
+
+    // after initialise: cars in an overfull zone move to the first zone with room, the cars that
+    // did not get a reservation (left in zone 0) go first, the others give up their reservations,
+    // which are then offered to the cars that can still take them
+    pub(crate) fn spread_over_capacity(&mut self, used: &[i32]) {
+        if self.capacity_mode != CapacityMode::Hard {
+            return;
+        }
+        let mut dropped: Vec<i32> = vec![];
+        for pass in 0..2 {
+            for veh_id in 0..self.vehicle.len() {
+                if (pass == 0 && used.contains(&(veh_id as i32))) || self.held_zone(veh_id as i32).is_some() {
+                    continue;
+                }
+                let zon_id = self.veh_to_zon[veh_id];
+                let full = self.zones[zon_id as usize]
+                    .capacity
+                    .is_some_and(|capacity| self.cars_in_zone(&self.veh_to_zon, zon_id) > capacity);
+                if !full {
+                    continue;
+                }
+                if let Some(zone) = self.zones.iter().map(|zone| zone.id).find(|zone| self.has_room(*zone)) {
+                    self.veh_to_zon[veh_id] = zone;
+                    dropped.extend(self.veh_to_res[veh_id].iter().copied());
+                    self.unassigned.append(&mut self.veh_to_res[veh_id]);
+                }
+            }
+        }
+
+        for res_id in dropped.iter().map(|res| *res as usize) {
+            let placed = (0..self.vehicle.len())
+                .any(|veh_id| self.vehicle_possible_own(veh_id, res_id) && self.set_vehicle_if_not_interfere(res_id, veh_id));
+            if !placed {
+                (0..self.vehicle.len()).any(|veh_id| {
+                    self.vehicle_possible_neighbour(veh_id, res_id) && self.set_vehicle_if_not_interfere(res_id, veh_id)
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structs::Constraints;
+    use crate::parser;
+
+    // every car of toy1 parked in z0, which has room for two, and nothing assigned
+    fn crowded() -> LocalSearch {
+        let (res, mut zone, veh) = parser::read_file("input/toy1.csv");
+        zone[0].capacity = Some(2);
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.veh_to_zon = vec![0; ls.vehicle.len()];
+        ls
+    }
+
+    #[test]
+    fn hard_capacity_rejects_an_overfull_zone() {
+        let mut ls = crowded();
+        assert!(!ls.has_room(0));
+        assert!(!ls.capacity_holds());
+        assert!(!ls.check_all());
+
+        ls.veh_to_zon = vec![0, 0, 1, 2, 3, 4];
+        assert!(ls.capacity_holds());
+        assert!(ls.check_all());
+        assert_eq!(ls.capacity_penalty(&ls.veh_to_zon), 0);
+    }
+
+    #[test]
+    fn soft_capacity_adds_its_penalty_per_car_over() {
+        let mut ls = crowded();
+        ls.capacity_mode = CapacityMode::Soft(7);
+        let unassigned: i32 = ls.reservations.iter().map(|res| res.p1).sum();
+
+        assert!(ls.capacity_holds());
+        assert!(ls.check_all());
+        assert_eq!(ls.calculate_full_cost(), unassigned + 4 * 7);
+        // the written cost leaves the penalty out
+        assert_eq!(ls.current_solution().cost, unassigned);
+
+        ls.veh_to_zon = vec![0, 0, 0, 1, 2, 3];
+        assert_eq!(ls.calculate_full_cost(), unassigned + 7);
+    }
+
+    #[test]
+    fn cars_held_over_a_hard_capacity_are_refused() {
+        let mut ls = crowded();
+        let fixed_zones = vec![(0, 0), (1, 0), (2, 0)];
+        ls.set_constraints(Constraints {
+            fixed_zones,
+            ..Constraints::default()
+        })
+        .unwrap();
+        assert!(ls.held_cars_fit().is_err());
+
+        ls.capacity_mode = CapacityMode::Soft(7);
+        assert!(ls.held_cars_fit().is_ok());
+    }
+
+    // car1 has to leave z0, the reservation it gives up goes to car0, held there
+    #[test]
+    fn reservations_of_moved_cars_are_placed_again() {
+        let mut ls = crowded();
+        ls.zones[0].capacity = Some(1);
+        ls.set_constraints(Constraints {
+            fixed_zones: vec![(0, 0)],
+            ..Constraints::default()
+        })
+        .unwrap();
+        ls.veh_to_zon = vec![0, 0, 1, 4, 4, 4];
+        ls.assign_veh_to_res(1, 5);
+
+        ls.spread_over_capacity(&[0, 1, 2]);
+        assert_eq!(ls.veh_to_zon[1], 1);
+        assert_eq!(ls.veh_to_res[0], vec![5]);
+        assert!(ls.check_all());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::data_structs::Solution;
+use crate::ls::LocalSearch;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// everything `run` needs to carry on where it stopped; the costs are the search objective
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub seed: u64,
+    pub word_pos: u128, // position of the random number generator in its stream
+    pub threshold: i32,
+    pub age: i32,
+    pub iterations: u64,
+    pub elapsed: f64,
+    pub time_to_best: f64,
+    pub best: Solution,
+    pub local: Solution,
+}
+
+impl Checkpoint {
+    pub fn rng(&self) -> ChaCha12Rng {
+        let mut r = ChaCha12Rng::seed_from_u64(self.seed);
+        r.set_word_pos(self.word_pos);
+        r
+    }
+}
+
+pub fn read_checkpoint(filepath: &str) -> Checkpoint {
+    let contents = fs::read_to_string(filepath).expect("could not read file");
+    serde_json::from_str(&contents).expect("could not parse checkpoint")
+}
+
+// write to a temporary file first so a kill never leaves half a checkpoint behind
+pub fn write_checkpoint(filepath: &str, checkpoint: &Checkpoint) -> std::io::Result<()> {
+    let tmp = format!("{}.tmp", filepath);
+    fs::write(&tmp, serde_json::to_string(checkpoint)?)?;
+    fs::rename(&tmp, filepath)
+}
+
+impl LocalSearch {
+    pub(crate) fn checkpoint(&self, seed: u64, r: &ChaCha12Rng, threshold: i32, age: i32, iterations: u64, elapsed: f64) -> Checkpoint {
+        Checkpoint {
+            seed,
+            word_pos: r.get_word_pos(),
+            threshold,
+            age,
+            iterations,
+            elapsed,
+            time_to_best: self.time_to_best,
+            best: self.best_state(),
+            local: self.committed_solution(),
+        }
+    }
+
+    pub(crate) fn load_checkpoint(&mut self, checkpoint: &Checkpoint) {
+        self.best_cost = checkpoint.best.cost;
+        self.best_veh_to_zon = checkpoint.best.veh_to_zon.clone();
+        self.best_veh_to_res = checkpoint.best.veh_to_res.clone();
+        self.best_unassigned = checkpoint.best.unassigned.clone();
+        self.local_veh_to_zon = checkpoint.local.veh_to_zon.clone();
+        self.local_veh_to_res = checkpoint.local.veh_to_res.clone();
+        self.local_unassigned = checkpoint.local.unassigned.clone();
+        self.restore();
+        self.local_cost = checkpoint.local.cost;
+        self.time_to_best = checkpoint.time_to_best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser;
+
+    // iterations count on from the checkpoint, so every resumed search gets a larger budget
+    fn search(checkpoint_file: &str, resume: Option<Checkpoint>, iterations: u64) -> LocalSearch {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.set_config(Config {
+            max_iterations: Some(iterations),
+            ..Config::default()
+        });
+        ls.checkpoint_file = Some(checkpoint_file.to_string());
+        ls.resume = resume;
+        ls
+    }
+
+    #[test]
+    fn resuming_keeps_the_saved_random_stream() {
+        let path = std::env::temp_dir().join("resume_seed.json");
+        let path = path.to_str().unwrap();
+        search(path, None, 300).run(3600, 1);
+        let first = read_checkpoint(path);
+
+        // resumed twice, the second time with another seed
+        search(path, Some(first.clone()), 600).run(3600, 7);
+        let second = read_checkpoint(path);
+        assert_eq!(second.seed, 1);
+        assert_eq!(second.iterations, 600);
+        assert!(second.word_pos > first.word_pos);
+        search(path, Some(second), 900).run(3600, 9);
+        assert_eq!(read_checkpoint(path).seed, 1);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::ls::LocalSearch;
+use crate::objectives::ObjectiveMode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// every tunable of the search; `balanced` matches the values `run` always used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub strategy: String,           // "threshold" (the search of `run`), "flow" (zones only), "memetic" or "ils"
+    pub constructor: String,        // "greedy", "demand", "regret", "grasp" or "matching"
+    pub grasp_alpha: f64,           // restricted candidate list of grasp, 0 is greedy and 1 random
+    pub exact_repair: bool,         // reschedule a moved car exactly instead of greedily only
+    pub polish: String,             // last step on the best solution: "optimise" or "schedule"
+    pub flow_rounds: i32,           // pricing rounds of the flow evaluation of the "flow" strategy
+    pub objectives: Option<String>, // "lex:...", "cost=1,..." or "pareto", replaces the strategy
+    pub moves_per_iteration: i32,   // random zone moves before the best is compared
+    pub stagnation_limit: i32,      // idle iterations before worse moves are accepted
+    pub threshold_step: i32,        // threshold per idle iteration once stagnating
+    pub initial_cost: i32,          // cost of the best solution before the first one is found
+    pub max_iterations: Option<u64>, // stop after this many moves as well, for reproducible runs
+    pub elite_size: usize,          // diverse solutions kept and relinked at the end, 0 keeps none
+    pub elite_distance: usize,      // cars in a different zone for two elite solutions to both stay,
+                                    // the same for two members of the memetic population
+    pub population_size: usize,     // zone layouts of the memetic strategy
+    pub crossover: String,          // "uniform" (per car) or "block" (per zone) crossover of layouts
+    pub mutation_rate: f64,         // chance of each car of an offspring to move to a neighbouring zone
+    pub offspring_moves: u64,       // zone moves of the local search improving every offspring
+    pub perturbation_strength: usize, // cars moved by a perturbation of the "ils" strategy
+    pub perturbation_max: usize,    // upper bound of the strength as it grows with every failure
+    pub adaptive_perturbation: bool, // grow the strength while the descent finds nothing better
+    pub acceptance: String,         // local optima "ils" continues from: "better" or "walk" (all)
+    pub restart_after: u32,         // iterations without a new best before "ils" restarts from it
+    pub checkpoint_interval: f64,   // seconds between two checkpoints
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::preset("balanced")
+    }
+}
+
+impl Config {
+    pub fn preset(name: &str) -> Config {
+        let balanced = Config {
+            strategy: "threshold".to_string(),
+            constructor: "greedy".to_string(),
+            grasp_alpha: 0.3,
+            exact_repair: false,
+            polish: "optimise".to_string(),
+            flow_rounds: 4,
+            objectives: None,
+            moves_per_iteration: 2,
+            stagnation_limit: 10,
+            threshold_step: 30,
+            initial_cost: 1000000,
+            max_iterations: None,
+            elite_size: 0,
+            elite_distance: 3,
+            population_size: 10,
+            crossover: "uniform".to_string(),
+            mutation_rate: 0.05,
+            offspring_moves: 100,
+            perturbation_strength: 3,
+            perturbation_max: 15,
+            adaptive_perturbation: true,
+            acceptance: "better".to_string(),
+            restart_after: 50,
+            checkpoint_interval: 60.0,
+        };
+        match name {
+            "balanced" => balanced,
+            "fast" => Config {
+                moves_per_iteration: 1,
+                stagnation_limit: 5,
+                threshold_step: 20,
+                ..balanced
+            },
+            "thorough" => Config {
+                moves_per_iteration: 4,
+                stagnation_limit: 20,
+                threshold_step: 40,
+                polish: "schedule".to_string(),
+                elite_size: 5,
+                ..balanced
+            },
+            _ => panic!("unknown preset {}", name),
+        }
+    }
+
+    pub fn within_budget(&self, iterations: u64) -> bool {
+        self.max_iterations.is_none_or(|max| iterations < max)
+    }
+
+    // only the threshold search of `run` writes checkpoints and resumes from them
+    pub fn checkpoints(&self) -> bool {
+        self.objectives.is_none() && self.strategy == "threshold"
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("could not serialise config")
+    }
+}
+
+// a config file may name a `preset`, the keys it sets override the preset (balanced if none)
+pub fn read_config(filepath: &str) -> Config {
+    let contents = fs::read_to_string(filepath).expect("could not read file");
+    let file: serde_json::Value = if filepath.ends_with(".toml") {
+        toml::from_str(&contents).expect("could not parse config")
+    } else {
+        serde_json::from_str(&contents).expect("could not parse config")
+    };
+    let file = file.as_object().expect("config is not a table").clone();
+
+    let preset = file.get("preset").and_then(|p| p.as_str()).unwrap_or("balanced");
+    let mut config = serde_json::to_value(Config::preset(preset)).unwrap();
+    for (key, value) in file {
+        if key != "preset" {
+            config[key] = value;
+        }
+    }
+    // a misspelled key is refused rather than leaving the preset value in place
+    serde_json::from_value(config).unwrap_or_else(|e| panic!("invalid config: {}", e))
+}
+
+pub fn write_config(filepath: &str, config: &Config) -> std::io::Result<()> {
+    if filepath.ends_with(".toml") {
+        fs::write(filepath, toml::to_string(config).expect("could not serialise config"))
+    } else {
+        fs::write(filepath, serde_json::to_string_pretty(config)?)
+    }
+}
+
+impl LocalSearch {
+    pub fn set_config(&mut self, config: Config) {
+        self.best_cost = config.initial_cost;
+        self.local_cost = config.initial_cost;
+        self.config = config;
+    }
+
+    // run the search the config describes; a pareto front only keeps its cheapest solution
+    pub fn solve(&mut self, time: i32, seed: u64) {
+        match self.config.objectives.clone().as_deref() {
+            Some("pareto") => {
+                self.run_pareto(time, seed);
+            }
+            Some(spec) => self.run_multi(time, seed, &ObjectiveMode::parse(spec)),
+            None => match self.config.strategy.as_str() {
+                "threshold" => self.run(time, seed),
+                "flow" => self.run_flow(time, seed),
+                "memetic" => self.run_memetic(time, seed),
+                "ils" => self.run_ils(time, seed),
+                strategy => panic!("unknown strategy {}", strategy),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_from(name: &str, contents: &str) -> Config {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        read_config(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn known_keys_override_the_preset() {
+        let config = config_from("config_known.toml", "preset = \"balanced\"\nstagnation_limit = 25\n");
+        assert_eq!(config.stagnation_limit, 25);
+        assert_eq!(config.strategy, Config::default().strategy);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown field `stagnaton_limit`")]
+    fn misspelled_keys_are_refused() {
+        config_from("config_misspelled.json", r#"{"stagnaton_limit": 25}"#);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::data_structs::{Constraints, Reservation, Vehicle};
+use crate::ls::{interfere, LocalSearch};
+
+// the reservation uses its car during (part of) the given day
+pub fn touches_day(res: &Reservation, day: i32) -> bool {
+    res.start < (day + 1) * 1440 && day * 1440 < res.start + res.restime
+}
+
+impl Vehicle {
+    // the reservation fits entirely inside one availability window, outside of them the car is in maintenance
+    pub fn available(&self, res: &Reservation) -> bool {
+        self.availability.is_empty()
+            || self
+                .availability
+                .iter()
+                .any(|(start, end)| *start <= res.start && res.start + res.restime <= *end)
+    }
+}
+
+impl Constraints {
+    pub fn fixed_zone(&self, veh_id: i32) -> Option<i32> {
+        self.fixed_zones
+            .iter()
+            .find(|(veh, _)| *veh == veh_id)
+            .map(|(_, zone)| *zone)
+    }
+
+    pub(crate) fn allows(&self, res: &Reservation, veh_id: i32) -> bool {
+        self.pinned.iter().all(|(r, v)| *r != res.id || *v == veh_id)
+            && !self.forbidden.contains(&(res.id, veh_id))
+            && !self
+                .out_of_service
+                .iter()
+                .any(|(v, day)| *v == veh_id && touches_day(res, *day))
+    }
+
+    pub fn check(&self, reservations: &[Reservation], veh_to_res: &[Vec<i32>], veh_to_zon: &[i32]) -> Result<(), String> {
+        for (res, veh) in &self.pinned {
+            if !veh_to_res.get(*veh as usize).is_some_and(|assigned| assigned.contains(res)) {
+                return Err(format!("req{} is pinned to car{}", res, veh));
+            }
+        }
+        for (veh, zone) in &self.fixed_zones {
+            if veh_to_zon.get(*veh as usize) != Some(zone) {
+                return Err(format!("car{} has to stay in z{}", veh, zone));
+            }
+        }
+        for (veh, assigned) in veh_to_res.iter().enumerate() {
+            for res in assigned {
+                if !self.allows(&reservations[*res as usize], veh as i32) {
+                    return Err(format!("req{} may not use car{}", res, veh));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LocalSearch {
+    // zone from which veh reaches every reservation pinned to it, its fixed zone when it has one,
+    // otherwise preferably the zone of its first pin
+    pub(crate) fn pinned_zone(&self, veh_id: i32) -> Option<i32> {
+        let pins: Vec<i32> = self
+            .constraints
+            .pinned
+            .iter()
+            .filter(|(_, veh)| *veh == veh_id)
+            .map(|(res, _)| self.reservations[*res as usize].zone)
+            .collect();
+        let candidates: Vec<i32> = match self.constraints.fixed_zone(veh_id).or(self.vehicle[veh_id as usize].zone) {
+            Some(zone) => vec![zone],
+            None => pins.iter().copied().chain(self.zones.iter().map(|zone| zone.id)).collect(),
+        };
+        candidates.into_iter().find(|zone| {
+            pins.iter()
+                .all(|zon_res| zon_res == zone || self.zones[*zone as usize].neighbours.contains(zon_res))
+        })
+    }
+
+    // constraints that could never hold are refused: pins and fixed zones naming nothing, a
+    // reservation pinned twice, or pins their car can not serve together from where it may park
+    pub fn set_constraints(&mut self, constraints: Constraints) -> Result<(), String> {
+        for (veh, zone) in &constraints.fixed_zones {
+            if *veh as usize >= self.vehicle.len() || *zone as usize >= self.zones.len() {
+                return Err(format!("car{} can not be fixed to z{}, no such car or zone", veh, zone));
+            }
+        }
+        for (k, (res, veh)) in constraints.pinned.iter().enumerate() {
+            if *res as usize >= self.reservations.len() || *veh as usize >= self.vehicle.len() {
+                return Err(format!("req{} can not be pinned to car{}, no such reservation or car", res, veh));
+            }
+            if constraints.pinned[..k].iter().any(|(other, _)| other == res) {
+                return Err(format!("req{} is pinned more than once", res));
+            }
+        }
+        self.constraints = constraints;
+
+        for (k, (res, veh)) in self.constraints.pinned.iter().enumerate() {
+            if !self.vehicle_possible(*veh as usize, *res as usize) {
+                return Err(format!("req{} is pinned to car{}, which can not serve it", res, veh));
+            }
+            if let Some((other, _)) = self.constraints.pinned[..k]
+                .iter()
+                .find(|(other, v)| v == veh && interfere(&self.reservations[*other as usize], &self.reservations[*res as usize]))
+            {
+                return Err(format!("req{} and req{} are both pinned to car{} but overlap", other, res, veh));
+            }
+            if self.pinned_zone(*veh).is_none() {
+                return Err(format!("car{} can not park where it reaches every reservation pinned to it", veh));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser;
+    use crate::validator::{validate, validate_constraints};
+
+    fn instance() -> LocalSearch {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        LocalSearch::new(res, zone, veh)
+    }
+
+    // the threshold search, with and without exact repair, and the flow strategy all keep to the constraints
+    fn solve_under(constraints: Constraints) {
+        for (strategy, exact_repair) in [("threshold", false), ("threshold", true), ("flow", false)] {
+            let mut ls = instance();
+            ls.set_config(Config {
+                strategy: strategy.to_string(),
+                exact_repair,
+                max_iterations: Some(500),
+                ..Config::default()
+            });
+            ls.set_constraints(constraints.clone()).unwrap();
+            ls.paranoid = true;
+            ls.solve(3600, 1);
+
+            let solution = ls.best_solution();
+            assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution), Ok(solution.cost));
+            assert_eq!(validate_constraints(&ls.reservations, &solution, &constraints), Ok(()));
+        }
+    }
+
+    #[test]
+    fn pinned_reservations_stay_on_their_car() {
+        // spread over several cars, a single car could not reach all of their zones
+        solve_under(Constraints {
+            pinned: vec![(1, 2), (20, 3), (40, 8)],
+            ..Constraints::default()
+        });
+    }
+
+    #[test]
+    fn pins_that_can_not_hold_are_refused() {
+        let refused = |pinned: Vec<(i32, i32)>, fixed_zones: Vec<(i32, i32)>| {
+            instance()
+                .set_constraints(Constraints {
+                    pinned,
+                    fixed_zones,
+                    ..Constraints::default()
+                })
+                .is_err()
+        };
+        let ls = instance();
+        let veh = ls.reservations[1].possible_vehicles[0];
+        let overlapping = (0..ls.reservations.len() as i32)
+            .find(|res| *res != 1 && interfere(&ls.reservations[1], &ls.reservations[*res as usize]))
+            .unwrap();
+        let zon_res = ls.reservations[1].zone;
+        let far = (0..ls.zones.len() as i32)
+            .find(|zone| *zone != zon_res && !ls.zones[*zone as usize].neighbours.contains(&zon_res))
+            .unwrap();
+
+        assert!(!refused(vec![(1, veh)], vec![]));
+        assert!(refused(vec![(1, veh), (1, veh)], vec![]));
+        assert!(refused(vec![(1, veh), (overlapping, veh)], vec![]));
+        assert!(refused(vec![(1, veh)], vec![(veh, far)]));
+        assert!(refused(vec![(1, ls.vehicle.len() as i32)], vec![]));
+    }
+
+    #[test]
+    fn fixed_zones_are_kept() {
+        solve_under(Constraints {
+            fixed_zones: vec![(0, 0), (3, 2), (7, 4)],
+            ..Constraints::default()
+        });
+    }
+
+    #[test]
+    fn forbidden_cars_are_not_used() {
+        let ls = instance();
+        let forbidden = ls
+            .reservations
+            .iter()
+            .take(30)
+            .filter_map(|res| res.possible_vehicles.first().map(|veh| (res.id, *veh)))
+            .collect();
+        solve_under(Constraints {
+            forbidden,
+            ..Constraints::default()
+        });
+    }
+
+    #[test]
+    fn cars_out_of_service_serve_nothing_that_day() {
+        solve_under(Constraints {
+            out_of_service: vec![(0, 0), (1, 1), (2, 0), (2, 1)],
+            ..Constraints::default()
+        });
+    }
+}
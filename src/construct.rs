@@ -0,0 +1,281 @@
+use crate::ls::LocalSearch;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+impl LocalSearch {
+    // cost of serving res with veh where it is parked, None when it cannot
+    fn insertion_cost(&self, veh: usize, res: usize) -> Option<i32> {
+        let reservation = &self.reservations[res];
+        let zone = self.veh_to_zon[veh];
+        let reach = zone == reservation.zone || self.zones[zone as usize].neighbours.contains(&reservation.zone);
+        if reach
+            && self.vehicle_possible(veh, res)
+            && !self.does_list_interfere(res, &self.veh_to_res[veh])
+        {
+            Some(self.calculate_cost(res as i32, veh))
+        } else {
+            None
+        }
+    }
+
+    // every way to serve res, cheapest first: a placed car where it stands, or a free car parked
+    // in the zone of res; placed cars win ties
+    fn insertions(&self, res: usize, free: &[bool]) -> Vec<(i32, usize)> {
+        let reservation = &self.reservations[res];
+        let mut options: Vec<(i32, usize)> = (0..self.vehicle.len())
+            .filter(|veh| self.vehicle_possible(*veh, res))
+            .filter_map(|veh| {
+                if free[veh] {
+                    self.has_room(reservation.zone)
+                        .then(|| (reservation.upgrade_penalty(&self.vehicle[veh]), veh))
+                } else {
+                    self.insertion_cost(veh, res).map(|cost| (cost, veh))
+                }
+            })
+            .collect();
+        options.sort_by_key(|(cost, veh)| (*cost, free[*veh]));
+        options
+    }
+
+    fn insert(&mut self, res: usize, veh: usize, free: &mut [bool]) {
+        if free[veh] {
+            free[veh] = false;
+            self.assign_zon_to_veh(veh as i32, self.reservations[res].zone);
+        }
+        self.assign_veh_to_res(veh as i32, res as i32);
+    }
+
+    fn free_cars(&self, used: &[i32]) -> Vec<bool> {
+        (0..self.vehicle.len()).map(|veh| !used.contains(&(veh as i32))).collect()
+    }
+
+    // common end of the constructors: the rest goes to the cheapest car that can take it, highest p1
+    // first, cars that are still free stay in zone 0
+    fn finish_construction(&mut self, free: &[bool]) {
+        let mut rest = self.unassigned.clone();
+        rest.sort_by_key(|res| -self.reservations[*res as usize].p1);
+        for res in rest {
+            let cheapest = (0..self.vehicle.len())
+                .filter_map(|veh| self.insertion_cost(veh, res as usize).map(|cost| (cost, veh)))
+                .min();
+            if let Some((_, veh)) = cheapest {
+                self.assign_veh_to_res(veh as i32, res);
+            }
+        }
+        let used: Vec<i32> = (0..free.len()).filter(|veh| !free[*veh]).map(|veh| veh as i32).collect();
+        self.spread_over_capacity(&used);
+    }
+
+    // highest summed p1 of the unassigned reservations of the zone running at the same time
+    fn peak_demand(&self, zon_id: i32) -> i32 {
+        let in_zone: Vec<usize> = self
+            .unassigned
+            .iter()
+            .map(|res| *res as usize)
+            .filter(|res| self.reservations[*res].zone == zon_id)
+            .collect();
+        in_zone
+            .iter()
+            .map(|a| {
+                let start = self.reservations[*a].start;
+                in_zone
+                    .iter()
+                    .map(|b| &self.reservations[*b])
+                    .filter(|b| b.start <= start && start <= b.start + b.restime)
+                    .map(|b| b.p1)
+                    .sum::<i32>()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // free cars go one by one to the zone with the highest peak demand per car already there
+    pub fn construct_demand(&mut self) {
+        let used = self.place_fixed_cars();
+        let mut free = self.free_cars(&used);
+        let peaks: Vec<i32> = self.zones.iter().map(|zone| self.peak_demand(zone.id)).collect();
+        let mut placed: Vec<i32> = vec![0; self.zones.len()];
+        for veh in used {
+            placed[self.veh_to_zon[veh as usize] as usize] += 1;
+        }
+
+        for (veh, is_free) in free.iter_mut().enumerate() {
+            if !*is_free {
+                continue;
+            }
+            let zone = self
+                .zones
+                .iter()
+                .map(|zone| zone.id)
+                .filter(|zone| {
+                    self.has_room(*zone)
+                        && self.unassigned.iter().any(|res| {
+                            self.reservations[*res as usize].zone == *zone && self.vehicle_possible(veh, *res as usize)
+                        })
+                })
+                .max_by(|a, b| {
+                    let a = peaks[*a as usize] as f64 / (placed[*a as usize] + 1) as f64;
+                    let b = peaks[*b as usize] as f64 / (placed[*b as usize] + 1) as f64;
+                    a.total_cmp(&b)
+                });
+            if let Some(zone) = zone {
+                *is_free = false;
+                placed[zone as usize] += 1;
+                self.assign_zon_to_veh(veh as i32, zone);
+            }
+        }
+        self.finish_construction(&free);
+    }
+
+    // the reservation that loses most when its best option goes (the second best option, or p1 when
+    // there is none) is inserted first
+    pub fn construct_regret(&mut self) {
+        let used = self.place_fixed_cars();
+        let mut free = self.free_cars(&used);
+        loop {
+            let mut choice: Option<(i32, i32, usize, usize)> = None;
+            for res in self.unassigned.iter().map(|res| *res as usize) {
+                let p1 = self.reservations[res].p1;
+                let options = self.insertions(res, &free);
+                let Some((best, veh)) = options.first().copied() else {
+                    continue;
+                };
+                if best >= p1 {
+                    continue;
+                }
+                let regret = options.get(1).map_or(p1, |(cost, _)| (*cost).min(p1)) - best;
+                if choice.is_none_or(|(r, b, _, _)| (regret, -best) > (r, -b)) {
+                    choice = Some((regret, best, res, veh));
+                }
+            }
+            match choice {
+                Some((_, _, res, veh)) => self.insert(res, veh, &mut free),
+                None => break,
+            }
+        }
+        self.finish_construction(&free);
+    }
+
+    // randomized greedy: every step inserts a random reservation among those whose gain (p1 minus
+    // the cheapest option) is within grasp_alpha of the best gain
+    pub fn construct_grasp(&mut self, seed: u64) {
+        let mut r = ChaCha12Rng::seed_from_u64(seed);
+        let used = self.place_fixed_cars();
+        let mut free = self.free_cars(&used);
+        loop {
+            let candidates: Vec<(i32, usize, usize)> = self
+                .unassigned
+                .iter()
+                .map(|res| *res as usize)
+                .filter_map(|res| {
+                    let (cost, veh) = self.insertions(res, &free).first().copied()?;
+                    let gain = self.reservations[res].p1 - cost;
+                    (gain > 0).then_some((gain, res, veh))
+                })
+                .collect();
+            let (Some(max), Some(min)) = (
+                candidates.iter().map(|c| c.0).max(),
+                candidates.iter().map(|c| c.0).min(),
+            ) else {
+                break;
+            };
+            let limit = max as f64 - self.config.grasp_alpha * (max - min) as f64;
+            let restricted: Vec<&(i32, usize, usize)> = candidates.iter().filter(|c| c.0 as f64 >= limit).collect();
+            let (_, res, veh) = *restricted[r.gen_range(0..restricted.len())];
+            self.insert(res, veh, &mut free);
+        }
+        self.finish_construction(&free);
+    }
+
+    // Kuhn's augmenting path from res over the candidate cars of every reservation
+    fn augment(res: usize, candidates: &[Vec<usize>], seen: &mut [bool], matched: &mut [Option<usize>]) -> bool {
+        for veh in &candidates[res] {
+            if seen[*veh] {
+                continue;
+            }
+            seen[*veh] = true;
+            if matched[*veh].is_none_or(|other| Self::augment(other, candidates, seen, matched)) {
+                matched[*veh] = Some(res);
+                return true;
+            }
+        }
+        false
+    }
+
+    // zone by zone, highest total p1 first: a maximum matching of the zone's reservations onto the
+    // cars parked there, repeated while it grows; a free car joins the zone only when the parked
+    // cars cannot take anything more, one at a time
+    pub fn construct_matching(&mut self) {
+        let used = self.place_fixed_cars();
+        let mut free = self.free_cars(&used);
+
+        let mut zones: Vec<i32> = self.zones.iter().map(|zone| zone.id).collect();
+        zones.sort_by_key(|zone| {
+            -self
+                .reservations
+                .iter()
+                .filter(|res| res.zone == *zone)
+                .map(|res| res.p1)
+                .sum::<i32>()
+        });
+
+        for zone in zones {
+            loop {
+                let mut in_zone: Vec<usize> = self
+                    .unassigned
+                    .iter()
+                    .map(|res| *res as usize)
+                    .filter(|res| self.reservations[*res].zone == zone)
+                    .collect();
+                in_zone.sort_by_key(|res| -self.reservations[*res].p1);
+
+                let mut inserted = false;
+                for with_free in [false, true] {
+                    let mut candidates: Vec<Vec<usize>> = vec![vec![]; self.reservations.len()];
+                    for res in &in_zone {
+                        candidates[*res] = self
+                            .insertions(*res, &free)
+                            .into_iter()
+                            .filter(|(_, veh)| {
+                                if free[*veh] {
+                                    with_free
+                                } else {
+                                    self.veh_to_zon[*veh] == zone
+                                }
+                            })
+                            .map(|(_, veh)| veh)
+                            .collect();
+                    }
+                    let mut matched: Vec<Option<usize>> = vec![None; self.vehicle.len()];
+                    for res in &in_zone {
+                        let mut seen = vec![false; self.vehicle.len()];
+                        Self::augment(*res, &candidates, &mut seen, &mut matched);
+                    }
+
+                    let mut pairs: Vec<(usize, usize)> = matched
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(veh, res)| res.map(|res| (res, veh)))
+                        .filter(|(_, veh)| free[*veh] == with_free)
+                        .collect();
+                    pairs.sort_by_key(|(res, _)| -self.reservations[*res].p1);
+                    if with_free {
+                        pairs.truncate(1);
+                    }
+                    for (res, veh) in &pairs {
+                        self.insert(*res, *veh, &mut free);
+                    }
+                    if !pairs.is_empty() {
+                        inserted = true;
+                        break;
+                    }
+                }
+                if !inserted {
+                    break;
+                }
+            }
+        }
+        self.finish_construction(&free);
+    }
+}
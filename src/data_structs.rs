@@ -1,27 +1,72 @@
-#[derive(Debug)]
-pub struct Zone
-{
-    pub id: i32,
-    pub neighbours: Vec<i32>
-}
-
-#[derive(Debug)]
-pub struct Vehicle
-{
-    pub id: i32,
-    pub zone: Option<i32>
-}
-
-#[derive(Debug)]
-pub struct Reservation
-{
-    pub id: i32,
-    pub zone: i32,
-    pub day: i32,
-    pub start: i32,
-    pub restime: i32,
-    pub possible_vehicles: Vec<i32>,
-    pub p1: i32,
-    pub p2: i32,
-    pub vehicle: Option<i32>
-}
\ No newline at end of file
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Zone
+{
+    pub id: i32,
+    pub neighbours: Vec<i32>,
+    #[serde(default)]
+    pub capacity: Option<i32>   // parking spaces, unlimited when absent
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vehicle
+{
+    pub id: i32,
+    pub zone: Option<i32>,                // home zone, where the car starts
+    #[serde(default)]
+    pub availability: Vec<(i32, i32)>,    // [start, end) in minutes from day 0, empty when always available
+    #[serde(default)]
+    pub category: Option<String>          // e.g. compact, van
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reservation
+{
+    pub id: i32,
+    pub zone: i32,
+    pub day: i32,
+    pub start: i32,
+    pub restime: i32,
+    pub possible_vehicles: Vec<i32>,
+    #[serde(default)]
+    pub categories: Vec<(String, i32)>,  // allowed vehicle categories with their upgrade penalty
+    pub p1: i32,
+    pub p2: i32,
+    #[serde(default = "default_priority")]
+    pub priority: i32,          // weight of serving the reservation in the multi-objective modes
+    pub vehicle: Option<i32>
+}
+
+fn default_priority() -> i32
+{
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Solution
+{
+    pub cost: i32,
+    pub veh_to_zon: Vec<i32>,
+    pub veh_to_res: Vec<Vec<i32>>,
+    pub unassigned: Vec<i32>
+}
+
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instance
+{
+    pub reservations: Vec<Reservation>,
+    pub zones: Vec<Zone>,
+    pub vehicles: Vec<Vehicle>
+}
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Constraints
+{
+    pub pinned: Vec<(i32, i32)>,         // (reservation, vehicle) the reservation must be served by
+    pub fixed_zones: Vec<(i32, i32)>,    // (vehicle, zone) the vehicle must stay in
+    pub forbidden: Vec<(i32, i32)>,      // (reservation, vehicle) that may never be combined
+    pub out_of_service: Vec<(i32, i32)>  // (vehicle, day) on which the vehicle can not be used
+}
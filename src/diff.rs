@@ -0,0 +1,226 @@
+use crate::data_structs::{Reservation, Solution, Vehicle};
+use serde::Serialize;
+
+// every change carries its contribution to the cost, `delta` is new minus old
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ZoneChange {
+    pub vehicle: i32,
+    pub from: i32,
+    pub to: i32,
+    pub delta: i32, // of the reservations the car serves in both solutions
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Move {
+    pub reservation: i32,
+    pub from: i32,
+    pub to: i32,
+    pub delta: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusChange {
+    pub reservation: i32,
+    pub assigned: bool, // served in the new solution, unassigned in the old one
+    pub vehicle: i32,   // the car serving it in whichever solution it is assigned
+    pub delta: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolutionDiff {
+    pub old_cost: i32,
+    pub new_cost: i32,
+    pub zones: Vec<ZoneChange>,
+    pub moved: Vec<Move>,
+    pub status: Vec<StatusChange>,
+}
+
+impl SolutionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty() && self.moved.is_empty() && self.status.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("diff is always serialisable")
+    }
+}
+
+// car serving each reservation, None when unassigned
+fn placement(reservations: &[Reservation], solution: &Solution) -> Vec<Option<usize>> {
+    let mut placed = vec![None; reservations.len()];
+    for (veh, assigned) in solution.veh_to_res.iter().enumerate() {
+        for res_id in assigned {
+            placed[*res_id as usize] = Some(veh);
+        }
+    }
+    placed
+}
+
+fn cost(res: &Reservation, vehicles: &[Vehicle], solution: &Solution, veh: Option<usize>) -> i32 {
+    match veh {
+        Some(veh) if res.zone != solution.veh_to_zon[veh] => res.p2 + res.upgrade_penalty(&vehicles[veh]),
+        Some(veh) => res.upgrade_penalty(&vehicles[veh]),
+        None => res.p1,
+    }
+}
+
+// both solutions have to be of the instance: one zone and one list per car, and as many
+// reservations as it has, none of them out of range
+fn matches(reservations: &[Reservation], vehicles: &[Vehicle], solution: &Solution, which: &str) -> Result<(), String> {
+    if solution.veh_to_zon.len() != vehicles.len() || solution.veh_to_res.len() != vehicles.len() {
+        return Err(format!("the {} solution does not have the {} cars of the instance", which, vehicles.len()));
+    }
+    let listed: Vec<i32> = solution.veh_to_res.iter().flatten().chain(&solution.unassigned).copied().collect();
+    if let Some(res) = listed.iter().find(|res| **res < 0 || **res as usize >= reservations.len()) {
+        return Err(format!("the {} solution lists req{}, which the instance does not have", which, res));
+    }
+    if listed.len() != reservations.len() {
+        return Err(format!(
+            "the {} solution lists {} reservations, the instance has {}",
+            which,
+            listed.len(),
+            reservations.len()
+        ));
+    }
+    Ok(())
+}
+
+pub fn diff(reservations: &[Reservation], vehicles: &[Vehicle], old: &Solution, new: &Solution) -> Result<SolutionDiff, String> {
+    matches(reservations, vehicles, old, "old")?;
+    matches(reservations, vehicles, new, "new")?;
+    let old_placed = placement(reservations, old);
+    let new_placed = placement(reservations, new);
+
+    let mut zones: Vec<ZoneChange> = (0..vehicles.len())
+        .filter(|veh| old.veh_to_zon[*veh] != new.veh_to_zon[*veh])
+        .map(|veh| ZoneChange {
+            vehicle: veh as i32,
+            from: old.veh_to_zon[veh],
+            to: new.veh_to_zon[veh],
+            delta: 0,
+        })
+        .collect();
+    let mut moved: Vec<Move> = vec![];
+    let mut status: Vec<StatusChange> = vec![];
+
+    for res in reservations {
+        let (before, after) = (old_placed[res.id as usize], new_placed[res.id as usize]);
+        let delta = cost(res, vehicles, new, after) - cost(res, vehicles, old, before);
+        match (before, after) {
+            // staying on its car only costs something different when the car moved
+            (Some(from), Some(to)) if from == to => {
+                if let Some(change) = zones.iter_mut().find(|change| change.vehicle == to as i32) {
+                    change.delta += delta;
+                }
+            }
+            (Some(from), Some(to)) => moved.push(Move {
+                reservation: res.id,
+                from: from as i32,
+                to: to as i32,
+                delta,
+            }),
+            (None, Some(veh)) | (Some(veh), None) => status.push(StatusChange {
+                reservation: res.id,
+                assigned: after.is_some(),
+                vehicle: veh as i32,
+                delta,
+            }),
+            (None, None) => {}
+        }
+    }
+
+    Ok(SolutionDiff {
+        old_cost: old.cost,
+        new_cost: new.cost,
+        zones,
+        moved,
+        status,
+    })
+}
+
+impl std::fmt::Display for SolutionDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "cost: {} -> {} ({:+})", self.old_cost, self.new_cost, self.new_cost - self.old_cost)?;
+        if self.is_empty() {
+            return writeln!(f, "solutions are identical");
+        }
+
+        writeln!(f, "\nzone changes: {}", self.zones.len())?;
+        for change in &self.zones {
+            writeln!(f, "    car{}: z{} -> z{} (delta {:+})", change.vehicle, change.from, change.to, change.delta)?;
+        }
+        writeln!(f, "\nmoved reservations: {}", self.moved.len())?;
+        for change in &self.moved {
+            writeln!(f, "    req{}: car{} -> car{} (delta {:+})", change.reservation, change.from, change.to, change.delta)?;
+        }
+        writeln!(f, "\nstatus changes: {}", self.status.len())?;
+        for change in &self.status {
+            if change.assigned {
+                writeln!(f, "    req{}: unassigned -> car{} (delta {:+})", change.reservation, change.vehicle, change.delta)?;
+            } else {
+                writeln!(f, "    req{}: car{} -> unassigned (delta {:+})", change.reservation, change.vehicle, change.delta)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ls::LocalSearch;
+    use crate::parser;
+
+    fn explained(changes: &SolutionDiff) -> i32 {
+        changes.zones.iter().map(|c| c.delta).sum::<i32>()
+            + changes.moved.iter().map(|c| c.delta).sum::<i32>()
+            + changes.status.iter().map(|c| c.delta).sum::<i32>()
+    }
+
+    #[test]
+    fn deltas_add_up_to_the_cost_difference() {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.construct(0);
+        let constructed = ls.current_solution();
+        assert!(diff(&ls.reservations, &ls.vehicle, &constructed, &constructed).unwrap().is_empty());
+
+        // a car parked in a neighbouring zone instead keeps the reservations of its old zone, which now cost p2
+        let veh = (0..ls.vehicle.len())
+            .find(|veh| !ls.veh_to_res[*veh].is_empty() && !ls.zones[ls.veh_to_zon[*veh] as usize].neighbours.is_empty())
+            .expect("a car with reservations");
+        ls.veh_to_zon[veh] = ls.zones[ls.veh_to_zon[veh] as usize].neighbours[0];
+        let moved = ls.current_solution();
+        let changes = diff(&ls.reservations, &ls.vehicle, &constructed, &moved).unwrap();
+        assert!(changes.zones.iter().any(|change| change.vehicle == veh as i32 && change.delta != 0));
+        assert_eq!(explained(&changes), moved.cost - constructed.cost);
+
+        ls.optimise();
+        let optimised = ls.current_solution();
+        let changes = diff(&ls.reservations, &ls.vehicle, &constructed, &optimised).unwrap();
+        assert!(!changes.zones.is_empty() && !changes.status.is_empty());
+        assert_eq!(explained(&changes), optimised.cost - constructed.cost);
+    }
+
+    #[test]
+    fn solutions_of_another_instance_are_refused() {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.construct(0);
+        let constructed = ls.current_solution();
+
+        let mut short = constructed.clone();
+        short.veh_to_zon.pop();
+        short.veh_to_res.pop();
+        assert!(diff(&ls.reservations, &ls.vehicle, &constructed, &short).is_err());
+
+        let mut unknown = constructed.clone();
+        unknown.unassigned.push(ls.reservations.len() as i32);
+        assert!(diff(&ls.reservations, &ls.vehicle, &unknown, &constructed).is_err());
+
+        let mut missing = constructed.clone();
+        missing.veh_to_res.iter_mut().for_each(|assigned| assigned.clear());
+        assert!(diff(&ls.reservations, &ls.vehicle, &constructed, &missing).is_err());
+    }
+}
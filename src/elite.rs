@@ -0,0 +1,194 @@
+use crate::data_structs::Solution;
+use crate::ls::LocalSearch;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+// number of cars parked in a different zone
+pub fn distance(veh_to_zon_1: &[i32], veh_to_zon_2: &[i32]) -> usize {
+    veh_to_zon_1
+        .iter()
+        .zip(veh_to_zon_2)
+        .filter(|(zone_1, zone_2)| zone_1 != zone_2)
+        .count()
+}
+
+// indices of the solutions in `pool` a solution of cost `cost` and layout `veh_to_zon` replaces, None
+// if it is rejected: it replaces the solutions closer than `min_distance` if it is cheaper than all of
+// them, otherwise it needs a free place or to beat the most expensive one; `pool` is cheapest first
+pub fn admission(pool: &[Solution], size: usize, min_distance: usize, cost: i32, veh_to_zon: &[i32]) -> Option<Vec<usize>> {
+    let close: Vec<usize> = (0..pool.len())
+        .filter(|k| distance(&pool[*k].veh_to_zon, veh_to_zon) < min_distance.max(1))
+        .collect();
+    if close.iter().any(|k| pool[*k].cost <= cost) {
+        return None;
+    }
+    if close.is_empty() && pool.len() >= size && pool.last().is_some_and(|worst| worst.cost <= cost) {
+        return None;
+    }
+    Some(close)
+}
+
+// insert a solution admitted with the indices it replaces, keeping the pool sorted and within size
+pub fn admit(pool: &mut Vec<Solution>, size: usize, replaced: Vec<usize>, solution: Solution) {
+    for k in replaced.into_iter().rev() {
+        pool.remove(k);
+    }
+    if pool.len() >= size {
+        pool.pop();
+    }
+    let index = pool.partition_point(|other| other.cost <= solution.cost);
+    pool.insert(index, solution);
+}
+
+// the elite pool holds up to `elite_size` solutions whose zone layouts differ in at least
+// `elite_distance` cars; their cost is the cost the search minimises, penalties included
+impl LocalSearch {
+    // offer the committed state to the elite pool
+    pub(crate) fn offer_elite(&mut self) {
+        if self.config.elite_size == 0 {
+            return;
+        }
+        let Some(replaced) = admission(
+            &self.elite,
+            self.config.elite_size,
+            self.config.elite_distance,
+            self.local_cost,
+            &self.local_veh_to_zon,
+        ) else {
+            return;
+        };
+        if !self.check_all() {
+            return;
+        }
+        let solution = self.committed_solution();
+        admit(&mut self.elite, self.config.elite_size, replaced, solution);
+    }
+
+    // walk from the committed state to the zone layout `target`, one car per step, each step taking
+    // the car whose move to its target zone is cheapest after the reservations are reassigned; the
+    // step taken is repaired by scheduling every car exactly. Returns the cheapest solution strictly
+    // between both ends, or the cheapest so far once the deadline passes
+    pub(crate) fn relink(&mut self, target: &[i32], deadline: Instant) -> Option<Solution> {
+        let mut differing: Vec<usize> = (0..self.vehicle.len())
+            .filter(|veh| self.veh_to_zon[*veh] != target[*veh] && self.movable(*veh))
+            .collect();
+        let mut best: Option<Solution> = None;
+
+        // the last step would arrive at the target itself
+        while differing.len() > 1 && !self.past(deadline) {
+            let mut step: Option<(i32, usize)> = None;
+            for (k, veh) in differing.iter().enumerate() {
+                self.car_to_zone(*veh as i32, target[*veh]);
+                let cost = self.calculate_full_cost();
+                if self.check_all() && step.is_none_or(|(best_cost, _)| cost < best_cost) {
+                    step = Some((cost, k));
+                }
+                self.restore();
+            }
+            let Some((_, k)) = step else {
+                break;
+            };
+
+            let veh = differing.swap_remove(k);
+            self.car_to_zone(veh as i32, target[veh]);
+            self.commit();
+            if self.schedule_all() && self.check_all() {
+                self.commit();
+            } else {
+                self.restore();
+            }
+            self.paranoid_check(|| format!("relink step car_to_zone(car{}, z{})", veh, target[veh]));
+            if best.as_ref().is_none_or(|best| self.local_cost < best.cost) {
+                best = Some(self.committed_solution());
+            }
+        }
+        best
+    }
+
+    fn past(&self, deadline: Instant) -> bool {
+        Instant::now() >= deadline || self.stop.load(Ordering::Relaxed)
+    }
+
+    // relink every elite solution towards every cheaper one, until the deadline or a stop request; the
+    // best solution of each walk is offered to the pool, true if the best solution improved
+    pub(crate) fn relink_elite(&mut self, deadline: Instant) -> bool {
+        let elite = self.elite.clone();
+        let mut improved = false;
+        for (k, from) in elite.iter().enumerate() {
+            for (j, to) in elite[..k].iter().enumerate() {
+                if self.past(deadline) {
+                    return improved;
+                }
+                self.load_state(from);
+                let Some(found) = self.relink(&to.veh_to_zon, deadline) else {
+                    continue;
+                };
+                self.load_state(&found);
+                self.offer_elite();
+                if self.local_cost < self.best_cost {
+                    self.promote_local_to_best();
+                    self.paranoid_check(|| format!("new best relinking elite {} towards elite {}", k, j));
+                    improved = true;
+                }
+            }
+        }
+        improved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser;
+
+    #[test]
+    fn pool_stays_sorted_diverse_and_feasible() {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.set_config(Config {
+            elite_size: 4,
+            max_iterations: Some(3000),
+            ..Config::default()
+        });
+        ls.paranoid = true;
+        ls.run(3600, 5);
+
+        assert!(ls.elite.len() > 1 && ls.elite.len() <= 4);
+        assert!(ls.elite.windows(2).all(|pair| pair[0].cost <= pair[1].cost));
+        assert!(ls.elite[0].cost >= ls.best_cost);
+        for (k, solution) in ls.elite.iter().enumerate() {
+            for other in &ls.elite[..k] {
+                assert!(distance(&solution.veh_to_zon, &other.veh_to_zon) >= 3);
+            }
+            ls.veh_to_res = solution.veh_to_res.clone();
+            ls.veh_to_zon = solution.veh_to_zon.clone();
+            ls.unassigned = solution.unassigned.clone();
+            assert!(ls.check_all());
+            assert_eq!(ls.calculate_full_cost(), solution.cost);
+        }
+    }
+
+    #[test]
+    fn relinking_stops_at_the_deadline_and_repairs_each_step() {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.set_config(Config {
+            elite_size: 4,
+            max_iterations: Some(3000),
+            ..Config::default()
+        });
+        ls.run(3600, 5);
+        let (from, to) = (ls.elite[1].clone(), ls.elite[0].clone());
+
+        ls.load_state(&from);
+        assert!(ls.relink(&to.veh_to_zon, Instant::now()).is_none());
+        assert!(!ls.relink_elite(Instant::now()));
+
+        ls.load_state(&from);
+        let found = ls.relink(&to.veh_to_zon, Instant::now() + std::time::Duration::from_secs(60)).unwrap();
+        ls.load_state(&found);
+        assert!(ls.check_all());
+        assert!(!ls.schedule_all());
+    }
+}
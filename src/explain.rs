@@ -0,0 +1,142 @@
+use crate::data_structs::{Reservation, Solution, Vehicle, Zone};
+use crate::ls::{interfere, LocalSearch};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    WrongZone { car_zone: i32 }, // car is parked neither in the zone of the reservation nor in a neighbour
+    Overlap { blocking: Vec<i32> }, // assigned reservations on the car that interfere
+    Free,                       // the car could take the reservation as is
+}
+
+#[derive(Debug, Clone)]
+pub struct VehicleExplanation {
+    pub vehicle: i32,
+    pub reason: Reason,
+    pub repair_delta: i32, // change in cost when the car is made to serve the reservation
+    pub repair: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub reservation: i32,
+    pub vehicles: Vec<VehicleExplanation>,
+}
+
+impl Explanation {
+    pub fn cheapest(&self) -> Option<&VehicleExplanation> {
+        self.vehicles.iter().min_by_key(|veh| veh.repair_delta)
+    }
+}
+
+fn cost_in_zone(reservations: &[Reservation], zones: &[Zone], veh: &Vehicle, res_id: i32, zon_veh: i32) -> Option<i32> {
+    let res = &reservations[res_id as usize];
+    if res.zone == zon_veh {
+        Some(res.upgrade_penalty(veh))
+    } else if zones[zon_veh as usize].neighbours.contains(&res.zone) {
+        Some(res.p2 + res.upgrade_penalty(veh))
+    } else {
+        None
+    }
+}
+
+// cost delta of serving res_id with veh parked in zon_veh, unassigning whatever no longer fits
+fn repair(reservations: &[Reservation], zones: &[Zone], vehicles: &[Vehicle], solution: &Solution, res_id: i32, veh: usize, zon_veh: i32) -> (i32, Vec<i32>) {
+    let res = &reservations[res_id as usize];
+    let old_zone = solution.veh_to_zon[veh];
+
+    let mut delta = cost_in_zone(reservations, zones, &vehicles[veh], res_id, zon_veh).unwrap() - res.p1;
+    let mut bumped: Vec<i32> = vec![];
+    for other in &solution.veh_to_res[veh] {
+        let old_cost = cost_in_zone(reservations, zones, &vehicles[veh], *other, old_zone).unwrap_or(0);
+        match cost_in_zone(reservations, zones, &vehicles[veh], *other, zon_veh) {
+            Some(new_cost) if !interfere(res, &reservations[*other as usize]) => {
+                delta += new_cost - old_cost;
+            }
+            _ => {
+                delta += reservations[*other as usize].p1 - old_cost;
+                bumped.push(*other);
+            }
+        }
+    }
+    (delta, bumped)
+}
+
+pub fn explain(reservations: &[Reservation], zones: &[Zone], vehicles: &[Vehicle], solution: &Solution, res_id: i32) -> Explanation {
+    let res = &reservations[res_id as usize];
+    let mut explained: Vec<VehicleExplanation> = vec![];
+
+    for veh in (0..vehicles.len()).filter(|veh| res.allows(&vehicles[*veh])) {
+        let zon_veh = solution.veh_to_zon[veh];
+
+        let (reason, zon_repair) = if cost_in_zone(reservations, zones, &vehicles[veh], res_id, zon_veh).is_none() {
+            (Reason::WrongZone { car_zone: zon_veh }, res.zone)
+        } else {
+            let blocking: Vec<i32> = solution.veh_to_res[veh]
+                .iter()
+                .filter(|other| interfere(res, &reservations[**other as usize]))
+                .copied()
+                .collect();
+            if blocking.is_empty() {
+                (Reason::Free, zon_veh)
+            } else {
+                (Reason::Overlap { blocking }, zon_veh)
+            }
+        };
+
+        let (repair_delta, bumped) = repair(reservations, zones, vehicles, solution, res_id, veh, zon_repair);
+        let mut repair = String::new();
+        if zon_repair != zon_veh {
+            repair.push_str(&format!("move car{} to z{}", veh, zon_repair));
+        }
+        if !bumped.is_empty() {
+            if !repair.is_empty() {
+                repair.push_str(", ");
+            }
+            let bumped: Vec<String> = bumped.iter().map(|b| format!("req{}", b)).collect();
+            repair.push_str(&format!("bump {}", bumped.join(", ")));
+        }
+        if repair.is_empty() {
+            repair.push_str(&format!("assign to car{}", veh));
+        }
+
+        explained.push(VehicleExplanation {
+            vehicle: veh as i32,
+            reason,
+            repair_delta,
+            repair,
+        });
+    }
+
+    Explanation {
+        reservation: res_id,
+        vehicles: explained,
+    }
+}
+
+pub fn explain_unassigned(reservations: &[Reservation], zones: &[Zone], vehicles: &[Vehicle], solution: &Solution) -> Vec<Explanation> {
+    solution
+        .unassigned
+        .iter()
+        .map(|res_id| explain(reservations, zones, vehicles, solution, *res_id))
+        .collect()
+}
+
+impl LocalSearch {
+    pub fn explain_unassigned(&self) -> Vec<Explanation> {
+        explain_unassigned(&self.reservations, &self.zones, &self.vehicle, &self.best_solution())
+    }
+}
+
+impl std::fmt::Display for VehicleExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reason {
+            Reason::WrongZone { car_zone } => write!(f, "car{}: parked in z{}, not in reach", self.vehicle, car_zone)?,
+            Reason::Overlap { blocking } => {
+                let blocking: Vec<String> = blocking.iter().map(|b| format!("req{}", b)).collect();
+                write!(f, "car{}: overlaps {}", self.vehicle, blocking.join(", "))?
+            }
+            Reason::Free => write!(f, "car{}: free", self.vehicle)?,
+        }
+        write!(f, " (repair: {}, delta {:+})", self.repair, self.repair_delta)
+    }
+}
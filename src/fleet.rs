@@ -0,0 +1,24 @@
+use crate::data_structs::{Reservation, Vehicle};
+
+impl Reservation {
+    // veh is listed explicitly or of an allowed category
+    pub fn allows(&self, veh: &Vehicle) -> bool {
+        self.possible_vehicles.contains(&veh.id)
+            || veh
+                .category
+                .as_ref()
+                .is_some_and(|category| self.categories.iter().any(|(cat, _)| cat == category))
+    }
+
+    // extra cost of serving the reservation with a car of a more expensive allowed category
+    pub fn upgrade_penalty(&self, veh: &Vehicle) -> i32 {
+        match &veh.category {
+            Some(category) => self
+                .categories
+                .iter()
+                .find(|(cat, _)| cat == category)
+                .map_or(0, |(_, penalty)| *penalty),
+            None => 0,
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use crate::capacity::CapacityMode;
+use crate::ls::LocalSearch;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+// min-cost flow by successive cheapest paths (SPFA, so negative arc costs are fine as long as the
+// network has no negative cycle)
+pub struct MinCostFlow {
+    graph: Vec<Vec<usize>>, // edges leaving every node
+    to: Vec<usize>,
+    cap: Vec<i32>,
+    cost: Vec<i64>,
+}
+
+impl MinCostFlow {
+    pub fn new() -> MinCostFlow {
+        MinCostFlow {
+            graph: vec![],
+            to: vec![],
+            cap: vec![],
+            cost: vec![],
+        }
+    }
+
+    pub fn add_node(&mut self) -> usize {
+        self.graph.push(vec![]);
+        self.graph.len() - 1
+    }
+
+    // the reverse edge of edge e is e ^ 1
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: i32, cost: i64) -> usize {
+        let edge = self.to.len();
+        self.graph[from].push(edge);
+        self.to.push(to);
+        self.cap.push(cap);
+        self.cost.push(cost);
+        self.graph[to].push(edge + 1);
+        self.to.push(from);
+        self.cap.push(0);
+        self.cost.push(-cost);
+        edge
+    }
+
+    pub fn flow(&self, edge: usize) -> i32 {
+        self.cap[edge ^ 1]
+    }
+
+    // augments along cheapest paths for as long as they lower the cost, returns (flow, cost)
+    pub fn run(&mut self, source: usize, sink: usize) -> (i32, i64) {
+        let mut flow = 0;
+        let mut total = 0;
+        loop {
+            let mut dist = vec![i64::MAX; self.graph.len()];
+            let mut prev = vec![usize::MAX; self.graph.len()];
+            let mut queued = vec![false; self.graph.len()];
+            let mut queue = VecDeque::from([source]);
+            dist[source] = 0;
+            while let Some(node) = queue.pop_front() {
+                queued[node] = false;
+                for edge in &self.graph[node] {
+                    let next = self.to[*edge];
+                    if self.cap[*edge] > 0 && dist[node] + self.cost[*edge] < dist[next] {
+                        dist[next] = dist[node] + self.cost[*edge];
+                        prev[next] = *edge;
+                        if !queued[next] {
+                            queued[next] = true;
+                            queue.push_back(next);
+                        }
+                    }
+                }
+            }
+            if dist[sink] >= 0 {
+                return (flow, total);
+            }
+
+            let mut push = i32::MAX;
+            let mut node = sink;
+            while node != source {
+                push = push.min(self.cap[prev[node]]);
+                node = self.to[prev[node] ^ 1];
+            }
+            let mut node = sink;
+            while node != source {
+                self.cap[prev[node]] -= push;
+                self.cap[prev[node] ^ 1] += push;
+                node = self.to[prev[node] ^ 1];
+            }
+            flow += push;
+            total += push as i64 * dist[sink];
+        }
+    }
+}
+
+impl Default for MinCostFlow {
+    fn default() -> MinCostFlow {
+        MinCostFlow::new()
+    }
+}
+
+// cars of one class as a time-expanded chain: a node per distinct start time, free arcs to
+// wait for the next one, and every reservation the zone can serve as an arc from its start to the
+// first start after its end, costing minus its gain (less its price); as many units of flow as
+// cars in the class
+struct ZoneChain {
+    network: MinCostFlow,
+    cars: Vec<usize>,
+    enter: usize, // edge from the source, its flow is the number of cars used
+    first: usize,
+    end: usize,
+    wait: Vec<usize>,                     // edge leaving every time node to the next one
+    serve: Vec<(usize, i32, usize, usize)>, // (edge, reservation, from node, to node)
+}
+
+impl LocalSearch {
+    fn zone_chain(&self, cars: &[usize], prices: &[i64]) -> Option<ZoneChain> {
+        let reservations: Vec<(i32, i64)> = (0..self.reservations.len() as i32)
+            .filter(|res| self.can_serve(cars[0], *res))
+            .map(|res| (res, self.gain(res, cars[0]) as i64 - prices[res as usize]))
+            .filter(|(_, gain)| *gain > 0)
+            .collect();
+        if reservations.is_empty() {
+            return None;
+        }
+
+        let mut network = MinCostFlow::new();
+        let source = network.add_node();
+        let sink = network.add_node();
+        let mut times: Vec<i32> = reservations.iter().map(|(res, _)| self.reservations[*res as usize].start).collect();
+        times.sort();
+        times.dedup();
+        let nodes: Vec<usize> = times.iter().map(|_| network.add_node()).collect();
+        let end = network.add_node();
+
+        let enter = network.add_edge(source, nodes[0], cars.len() as i32, 0);
+        let mut wait = vec![];
+        for k in 0..nodes.len() {
+            let next = nodes.get(k + 1).copied().unwrap_or(end);
+            wait.push(network.add_edge(nodes[k], next, cars.len() as i32, 0));
+        }
+        network.add_edge(end, sink, cars.len() as i32, 0);
+
+        let mut serve = vec![];
+        for (res, gain) in reservations {
+            let reservation = &self.reservations[res as usize];
+            let from = nodes[times.partition_point(|t| *t < reservation.start)];
+            let after = times.partition_point(|t| *t <= reservation.start + reservation.restime);
+            let to = nodes.get(after).copied().unwrap_or(end);
+            serve.push((network.add_edge(from, to, 1, -gain), res, from, to));
+        }
+        network.run(source, sink);
+        Some(ZoneChain {
+            network,
+            cars: cars.to_vec(),
+            enter,
+            first: nodes[0],
+            end,
+            wait,
+            serve,
+        })
+    }
+
+    // the reservations every unit of flow picks up on its way through the chain
+    fn chains(chain: &ZoneChain) -> Vec<Vec<i32>> {
+        let network = &chain.network;
+        // the time nodes are numbered consecutively, the end node comes right after the last one
+        let mut waiting: Vec<i32> = chain.wait.iter().map(|edge| network.flow(*edge)).collect();
+        let mut leaving: Vec<Vec<i32>> = vec![vec![]; chain.wait.len()];
+        for (edge, res, from, _) in &chain.serve {
+            if network.flow(*edge) > 0 {
+                leaving[from - chain.first].push(*res);
+            }
+        }
+        let to: HashMap<i32, usize> = chain.serve.iter().map(|(_, res, _, to)| (*res, *to)).collect();
+
+        let mut chains = vec![];
+        for _ in 0..network.flow(chain.enter) {
+            let mut node = chain.first;
+            let mut picked = vec![];
+            while node != chain.end {
+                let k = node - chain.first;
+                if let Some(res) = leaving[k].pop() {
+                    picked.push(res);
+                    node = to[&res];
+                } else if waiting[k] > 0 {
+                    waiting[k] -= 1;
+                    node += 1;
+                } else {
+                    break;
+                }
+            }
+            if !picked.is_empty() {
+                chains.push(picked);
+            }
+        }
+        chains
+    }
+
+    // heuristic assignment of all reservations for the current zones: every class of alike cars gets
+    // a flow of its own, optimal for that class alone, and the classes compete for reservations
+    // through prices over `flow_rounds` rounds; one still picked twice goes to the car gaining most
+    // from it, then every car is scheduled exactly. A single network shared by all classes is no
+    // exact alternative, a unit of flow could leave a reservation on another car than it came in on
+    pub fn assign_by_priced_flow(&mut self) {
+        // cars of a zone that serve the same reservations for the same gain are interchangeable
+        let mut signatures: Vec<Vec<(i32, i32)>> = vec![];
+        let mut classes: Vec<Vec<usize>> = vec![];
+        for veh in 0..self.vehicle.len() {
+            let signature: Vec<(i32, i32)> = (0..self.reservations.len() as i32)
+                .filter(|res| self.can_serve(veh, *res))
+                .map(|res| (res, self.gain(res, veh)))
+                .collect();
+            let class = (0..classes.len())
+                .find(|c| self.veh_to_zon[classes[*c][0]] == self.veh_to_zon[veh] && signatures[*c] == signature);
+            match class {
+                Some(c) => classes[c].push(veh),
+                None => {
+                    signatures.push(signature);
+                    classes.push(vec![veh]);
+                }
+            }
+        }
+
+        // a reservation picked by chains of several classes gets a price, doubled every round it
+        // is still picked twice
+        let mut prices: Vec<i64> = vec![0; self.reservations.len()];
+        let mut picked: Vec<(Vec<usize>, Vec<i32>)> = vec![];
+        for _ in 0..self.config.flow_rounds.max(1) {
+            // the classes only share source and sink, so every class gets a network of its own
+            let chains: Vec<ZoneChain> = classes
+                .iter()
+                .filter_map(|cars| self.zone_chain(cars, &prices))
+                .collect();
+
+            picked = vec![];
+            for chain in &chains {
+                for reservations in Self::chains(chain) {
+                    picked.push((chain.cars.clone(), reservations));
+                }
+            }
+            let mut count = vec![0; self.reservations.len()];
+            for res in picked.iter().flat_map(|(_, reservations)| reservations) {
+                count[*res as usize] += 1;
+            }
+            if count.iter().all(|c| *c <= 1) {
+                break;
+            }
+            for (res, c) in count.iter().enumerate() {
+                if *c > 1 {
+                    prices[res] = 2 * prices[res] + 1;
+                }
+            }
+        }
+
+        for reservations in self.veh_to_res.iter_mut() {
+            reservations.clear();
+        }
+        self.unassigned = (0..self.reservations.len() as i32).collect();
+        for res in self.unassigned.clone() {
+            // a car moved out of reach leaves it unassigned, which check_all rejects
+            if let Some(veh) = self.tied_to(res).filter(|veh| self.can_serve(*veh, res)) {
+                self.set_vehicle_if_not_interfere(res as usize, veh);
+            }
+        }
+
+        // the cars of a class are alike, so its chains go to its cars in turn; a reservation picked
+        // more than once goes to the car that gains most from it
+        let mut chain_of: Vec<Vec<i32>> = vec![vec![]; self.vehicle.len()];
+        let mut next: Vec<usize> = vec![0; self.vehicle.len()];
+        for (cars, reservations) in picked {
+            let k = &mut next[cars[0]];
+            chain_of[cars[*k]] = reservations;
+            *k += 1;
+        }
+        let mut rest: Vec<i32> = chain_of.iter().flatten().copied().collect();
+        rest.sort_by_key(|res| -self.reservations[*res as usize].p1);
+        rest.dedup();
+        for res in rest {
+            let mut cars: Vec<usize> = (0..self.vehicle.len()).filter(|veh| chain_of[*veh].contains(&res)).collect();
+            cars.sort_by_key(|veh| -self.gain(res, *veh));
+            for veh in cars {
+                if self.set_vehicle_if_not_interfere(res as usize, veh) {
+                    break;
+                }
+            }
+        }
+        self.schedule_all();
+    }
+
+    // threshold search over the zones only, every layout evaluated with assign_by_priced_flow
+    pub fn run_flow(&mut self, time: i32, seed: u64) {
+        let mut r = ChaCha12Rng::seed_from_u64(seed);
+        self.construct(seed);
+        self.assign_by_priced_flow();
+        self.commit();
+        if self.check_all() {
+            self.promote_local_to_best();
+        }
+
+        let start_time = Instant::now();
+        let mut threshold = 0;
+        let mut age = 1;
+        let mut i = 0;
+        while start_time.elapsed().as_secs_f64() < time as f64
+            && !self.stop.load(Ordering::Relaxed)
+            && self.config.within_budget(i)
+        {
+            i += 1;
+            let vehicle_id: usize = (r.gen::<u16>() % self.vehicle.len() as u16) as usize;
+            let neighbours = self.zones[self.veh_to_zon[vehicle_id] as usize].neighbours.clone();
+            if neighbours.is_empty() || !self.movable(vehicle_id) {
+                continue;
+            }
+            let zone = neighbours[r.gen_range(0..neighbours.len())];
+            if self.strands_tied(vehicle_id, zone) {
+                continue;
+            }
+            // the zones change as in move_car, the flow then assigns every reservation anew
+            let old_zone = self.veh_to_zon[vehicle_id];
+            self.veh_to_zon[vehicle_id] = zone;
+            if self.capacity_mode == CapacityMode::Hard && !self.capacity_holds() {
+                if let Some(other) = self.swap_partner(vehicle_id, zone, old_zone) {
+                    self.veh_to_zon[other] = old_zone;
+                }
+            }
+            self.assign_by_priced_flow();
+            self.paranoid_check(|| format!("assign_by_priced_flow after car{} moved to z{}", vehicle_id, zone));
+
+            let cost = self.calculate_full_cost();
+            if self.check_all() && cost < self.local_cost + threshold {
+                self.commit();
+                age = 1;
+                if self.local_cost < self.best_cost {
+                    self.promote_local_to_best();
+                    self.time_to_best = start_time.elapsed().as_secs_f64();
+                    self.improved(self.time_to_best, i);
+                }
+            } else {
+                self.restore();
+                age += 1;
+            }
+
+            threshold = 0;
+            if age > self.config.stagnation_limit {
+                threshold = age * self.config.threshold_step;
+            }
+        }
+        self.iterations = i;
+
+        self.log(format!("\ncost_end: {} after {i} iterations", self.best_cost));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::data_structs::Constraints;
+    use crate::ls::LocalSearch;
+    use crate::parser;
+    use crate::validator::{validate, validate_constraints};
+
+    #[test]
+    fn pinned_reservation_stays_reachable() {
+        let constraints = Constraints {
+            pinned: vec![(1, 2)],
+            ..Constraints::default()
+        };
+        for seed in 1..=3 {
+            let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+            let mut ls = LocalSearch::new(res, zone, veh);
+            ls.set_config(Config {
+                strategy: "flow".to_string(),
+                max_iterations: Some(200),
+                ..Config::default()
+            });
+            ls.set_constraints(constraints.clone()).unwrap();
+            ls.paranoid = true;
+            ls.solve(3600, seed);
+
+            let solution = ls.best_solution();
+            assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution), Ok(solution.cost));
+            assert_eq!(validate_constraints(&ls.reservations, &solution, &constraints), Ok(()));
+        }
+    }
+
+    // every zone is as full as the constructed layout leaves it, so each zone move has to swap
+    #[test]
+    fn full_zones_are_entered_by_swapping() {
+        let (res, mut zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut constructed = LocalSearch::new(res.clone(), zone.clone(), veh.clone());
+        constructed.construct(1);
+        for zone in zone.iter_mut() {
+            zone.capacity = Some(constructed.cars_in_zone(&constructed.veh_to_zon, zone.id));
+        }
+
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.set_config(Config {
+            strategy: "flow".to_string(),
+            max_iterations: Some(200),
+            ..Config::default()
+        });
+        ls.paranoid = true;
+        ls.solve(3600, 1);
+
+        let solution = ls.best_solution();
+        assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution), Ok(solution.cost));
+        assert_ne!(solution.veh_to_zon, constructed.veh_to_zon);
+        ls.load_state(&ls.best_state());
+        assert!(ls.capacity_holds());
+    }
+}
@@ -0,0 +1,253 @@
+use crate::ls::LocalSearch;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+// what one restart of the iterated local search achieved; the first restart starts from the
+// constructed solution, every later one from the best solution
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RestartStats {
+    pub restart: usize,
+    pub iterations: u64, // perturbations followed by a descent
+    pub accepted: u64,
+    pub start_cost: i32,
+    pub best_cost: i32,
+    pub strength: usize, // cars moved by the last perturbation
+    pub seconds: f64,
+}
+
+impl std::fmt::Display for RestartStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "restart {}: {} iterations, {} accepted, cost {} -> {}, strength {}, {:.2}s",
+            self.restart, self.iterations, self.accepted, self.start_cost, self.best_cost, self.strength, self.seconds
+        )
+    }
+}
+
+// iterated local search: a perturbation of `strength` random zone moves, then a descent with every
+// operator; iterations count the zone moves, like in `run`
+impl LocalSearch {
+    // zone moves of every car, then reassigning single reservations and scheduling every car exactly,
+    // until none of them improves; only improving steps are taken
+    fn descend(&mut self, start_time: &Instant, time: i32, i: &mut u64) {
+        loop {
+            let mut improved = false;
+            for veh in 0..self.vehicle.len() {
+                if !self.running(start_time, time, *i) {
+                    return;
+                }
+                *i += 1;
+                improved |= self.try_neighbour_zones(veh, 0);
+            }
+            if self.optimise() | self.schedule_all() {
+                if self.check_all() && self.calculate_full_cost() < self.local_cost {
+                    self.commit();
+                    improved = true;
+                } else {
+                    self.restore();
+                }
+            }
+            if !improved {
+                return;
+            }
+        }
+    }
+
+    // move `strength` random cars that may move to a random neighbouring zone; false, with the
+    // committed state restored, if that is infeasible
+    fn perturb(&mut self, strength: usize, i: &mut u64, r: &mut ChaCha12Rng) -> bool {
+        let cars: Vec<usize> = (0..self.vehicle.len()).filter(|veh| self.movable(*veh)).collect();
+        if cars.is_empty() {
+            return false;
+        }
+        for _ in 0..strength {
+            *i += 1;
+            let veh = cars[r.gen_range(0..cars.len())];
+            let neighbours = &self.zones[self.veh_to_zon[veh] as usize].neighbours;
+            if neighbours.is_empty() {
+                continue;
+            }
+            let zone = neighbours[r.gen_range(0..neighbours.len())];
+            self.move_car(veh, zone);
+        }
+        self.paranoid_check(|| format!("perturbation of strength {}", strength));
+        if !self.check_all() {
+            self.restore();
+            return false;
+        }
+        self.commit();
+        true
+    }
+
+    // acceptance "better" keeps a local optimum cheaper than the current one, "walk" keeps every one;
+    // after `restart_after` iterations without a new best the search restarts from the best solution
+    pub fn run_ils(&mut self, time: i32, seed: u64) {
+        let mut r = ChaCha12Rng::seed_from_u64(seed);
+        let start_time = Instant::now();
+        let mut i = 0;
+
+        self.construct(seed);
+        self.commit();
+        self.descend(&start_time, time, &mut i);
+        if self.check_all() {
+            self.promote_local_to_best();
+            self.time_to_best = start_time.elapsed().as_secs_f64();
+            self.improved(self.time_to_best, i);
+        }
+        self.offer_elite();
+
+        let min_strength = self.config.perturbation_strength.max(1);
+        let max_strength = self.config.perturbation_max.max(min_strength);
+        let mut current = self.committed_solution();
+        let mut strength = min_strength;
+        let mut failures = 0;
+        let mut stats = RestartStats {
+            restart: 0,
+            iterations: 0,
+            accepted: 0,
+            start_cost: current.cost,
+            best_cost: current.cost,
+            strength,
+            seconds: 0.0,
+        };
+        let mut restart_time = start_time.elapsed().as_secs_f64();
+        self.restarts = vec![];
+
+        while self.running(&start_time, time, i) {
+            stats.iterations += 1;
+            stats.strength = strength;
+            if self.perturb(strength, &mut i, &mut r) {
+                self.descend(&start_time, time, &mut i);
+                self.offer_elite();
+            }
+            let cost = self.local_cost;
+            stats.best_cost = stats.best_cost.min(cost);
+
+            if cost < self.best_cost {
+                self.promote_local_to_best();
+                self.time_to_best = start_time.elapsed().as_secs_f64();
+                self.improved(self.time_to_best, i);
+                self.paranoid_check(|| format!("new best after perturbation of strength {}", strength));
+                failures = 0;
+            } else {
+                failures += 1;
+            }
+
+            if cost < current.cost {
+                strength = min_strength;
+            } else if self.config.adaptive_perturbation {
+                strength = (strength + 1).min(max_strength);
+            }
+
+            let accept = match self.config.acceptance.as_str() {
+                "better" => cost < current.cost,
+                "walk" => true,
+                acceptance => panic!("unknown acceptance {}", acceptance),
+            };
+            if accept {
+                stats.accepted += 1;
+                current = self.committed_solution();
+            } else {
+                self.load_state(&current);
+            }
+
+            if failures >= self.config.restart_after {
+                stats.seconds = start_time.elapsed().as_secs_f64() - restart_time;
+                self.log(&stats);
+                self.restarts.push(stats.clone());
+
+                current = self.best_state();
+                self.load_state(&current);
+                strength = min_strength;
+                failures = 0;
+                restart_time = start_time.elapsed().as_secs_f64();
+                stats = RestartStats {
+                    restart: stats.restart + 1,
+                    iterations: 0,
+                    accepted: 0,
+                    start_cost: current.cost,
+                    best_cost: current.cost,
+                    strength,
+                    seconds: 0.0,
+                };
+            }
+        }
+        stats.seconds = start_time.elapsed().as_secs_f64() - restart_time;
+        self.log(&stats);
+        self.restarts.push(stats);
+
+        let relinked = self.relink_elite(start_time + Duration::from_secs_f64(time as f64));
+        let polished = self.polish_best();
+        if relinked || polished {
+            self.time_to_best = start_time.elapsed().as_secs_f64();
+            self.improved(self.time_to_best, i);
+        }
+        self.iterations = i;
+
+        self.log(format!("\ncost_end: {} after {i} iterations", self.best_cost));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::data_structs::Constraints;
+    use crate::ls::LocalSearch;
+    use crate::parser;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn every_restart_starts_from_the_best_so_far() {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.set_config(Config {
+            strategy: "ils".to_string(),
+            acceptance: "walk".to_string(),
+            restart_after: 5,
+            max_iterations: Some(5000),
+            ..Config::default()
+        });
+        ls.paranoid = true;
+        ls.solve(3600, 2);
+
+        assert!(ls.restarts.len() > 1);
+        let mut best = ls.restarts[0].best_cost;
+        for (k, stats) in ls.restarts.iter().enumerate().skip(1) {
+            assert_eq!(stats.restart, k);
+            assert_eq!(stats.start_cost, best);
+            assert!(stats.best_cost <= stats.start_cost);
+            assert!(stats.strength >= 3 && stats.strength <= 15);
+            best = stats.best_cost;
+        }
+        assert!(ls.best_cost <= best);
+    }
+
+    #[test]
+    fn perturbation_only_picks_cars_that_may_move() {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.construct(0);
+        ls.commit();
+        // every car but the last keeps its zone
+        let fixed_zones = (0..ls.vehicle.len() - 1).map(|veh| (veh as i32, ls.veh_to_zon[veh])).collect();
+        ls.set_constraints(Constraints {
+            fixed_zones,
+            ..Constraints::default()
+        })
+        .unwrap();
+
+        let mut r = ChaCha12Rng::seed_from_u64(0);
+        let mut i = 0;
+        for _ in 0..10 {
+            let before = ls.local_veh_to_zon.clone();
+            assert!(ls.perturb(1, &mut i, &mut r));
+            assert_ne!(ls.local_veh_to_zon, before);
+        }
+        assert_eq!(i, 10);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::ls::{interfere, LocalSearch};
+
+impl LocalSearch {
+    // cost of any state, computed from scratch
+    fn cost_of(&self, veh_to_res: &[Vec<i32>], veh_to_zon: &[i32], unassigned: &[i32]) -> i32 {
+        let mut sum = 0;
+        for (veh_id, reservations) in veh_to_res.iter().enumerate() {
+            for res in reservations {
+                let res = &self.reservations[*res as usize];
+                sum += res.upgrade_penalty(&self.vehicle[veh_id]);
+                if res.zone != veh_to_zon[veh_id] {
+                    sum += res.p2;
+                }
+            }
+        }
+        for res in unassigned {
+            sum += self.reservations[*res as usize].p1;
+        }
+        sum + self.penalty(veh_to_res, veh_to_zon, unassigned)
+    }
+
+    // the live state is consistent and the cached costs of the committed and the best state are right;
+    // the first broken invariant as an error
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut seen: Vec<Option<String>> = vec![None; self.reservations.len()];
+        let mut see = |res: i32, place: String| -> Result<(), String> {
+            let slot = seen
+                .get_mut(res as usize)
+                .ok_or(format!("req{} in {} does not exist", res, place))?;
+            if let Some(other) = slot {
+                return Err(format!("req{} is both in {} and in {}", res, other, place));
+            }
+            *slot = Some(place);
+            Ok(())
+        };
+
+        for (veh_id, reservations) in self.veh_to_res.iter().enumerate() {
+            for res in reservations {
+                see(*res, format!("car{}", veh_id))?;
+            }
+        }
+        for res in &self.unassigned {
+            see(*res, "unassigned".to_string())?;
+        }
+        for res in &self.cancelled {
+            if seen.get(*res as usize) != Some(&Some("unassigned".to_string())) {
+                return Err(format!("cancelled req{} is not unassigned", res));
+            }
+        }
+        if let Some(res) = seen.iter().position(|place| place.is_none()) {
+            return Err(format!("req{} is nowhere", res));
+        }
+
+        for (veh_id, reservations) in self.veh_to_res.iter().enumerate() {
+            let zone = self.veh_to_zon[veh_id];
+            for res_1 in reservations {
+                let res = &self.reservations[*res_1 as usize];
+                if !self.vehicle_possible(veh_id, *res_1 as usize) {
+                    return Err(format!("req{} is on car{}, not one of its possible vehicles", res_1, veh_id));
+                }
+                if res.zone != zone && !self.zones[zone as usize].neighbours.contains(&res.zone) {
+                    return Err(format!("req{} in z{} is on car{} parked in z{}", res_1, res.zone, veh_id, zone));
+                }
+                for res_2 in reservations {
+                    if res_1 < res_2 && interfere(res, &self.reservations[*res_2 as usize]) {
+                        return Err(format!("req{} and req{} overlap on car{}", res_1, res_2, veh_id));
+                    }
+                }
+            }
+        }
+
+        // before the first solution the costs still hold the initial sentinel
+        if self.local_cost != self.config.initial_cost {
+            let cost = self.cost_of(&self.local_veh_to_res, &self.local_veh_to_zon, &self.local_unassigned);
+            if cost != self.local_cost {
+                return Err(format!("cached local cost {} but the committed state costs {}", self.local_cost, cost));
+            }
+        }
+        if self.best_cost != self.config.initial_cost {
+            let cost = self.cost_of(&self.best_veh_to_res, &self.best_veh_to_zon, &self.best_unassigned);
+            if cost != self.best_cost {
+                return Err(format!("cached best cost {} but the best state costs {}", self.best_cost, cost));
+            }
+        }
+        Ok(())
+    }
+
+    // in paranoid mode, stop at the first move that breaks an invariant; `operation` describes the
+    // move with its inputs and is only built then
+    pub(crate) fn paranoid_check<F: FnOnce() -> String>(&self, operation: F) {
+        if !self.paranoid {
+            return;
+        }
+        if let Err(e) = self.check_invariants() {
+            panic!("invariant broken after {}: {}", operation(), e);
+        }
+    }
+}
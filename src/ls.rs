@@ -1,499 +1,913 @@
-use crate::data_structs::{Reservation, Vehicle, Zone};
-extern crate rand;
-use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
-use rand::Rng;
-use rand::SeedableRng;
-use std::{fs::File, io::Write, time::Instant};
-
-pub struct LocalSearch {
-    pub reservations: Vec<Reservation>,
-    pub zones: Vec<Zone>,
-    pub vehicle: Vec<Vehicle>,
-
-    pub veh_to_res: Vec<Vec<i32>>, // given vehicle, get list of reservations that is assigned to it
-    pub veh_to_zon: Vec<i32>,      // given vehicle, get zone that is assigned
-    pub unassigned: Vec<i32>,
-
-    pub best_cost: i32,
-    pub best_veh_to_res: Vec<Vec<i32>>,
-    pub best_veh_to_zon: Vec<i32>,
-    pub best_unassigned: Vec<i32>,
-
-    pub local_cost: i32,
-    pub local_veh_to_res: Vec<Vec<i32>>,
-    pub local_veh_to_zon: Vec<i32>,
-    pub local_unassigned: Vec<i32>,
-}
-
-impl LocalSearch {
-    fn does_interfere(&self, res1: usize, res2: usize) -> bool {
-        let res_1: &Reservation = &self.reservations[res1];
-        let res_2: &Reservation = &self.reservations[res2];
-
-        // if res_1.day != res_2.day {
-        //     return false;
-        // }
-
-        let start_1 = res_1.start;
-        let end_1 = start_1 + res_1.restime;
-        let start_2 = res_2.start;
-        let end_2 = start_2 + res_2.restime;
-
-        if (start_1 <= start_2) && (start_2 <= end_1) {
-            return true;
-        } else if (start_1 <= end_2) && (end_2 <= end_1) {
-            return true;
-        } else if (start_1 <= end_2) && (start_2 <= end_1) {
-            return true;
-        }
-        return false;
-    }
-
-    fn does_list_interfere(&self, res_1: usize, res_list: &Vec<i32>) -> bool {
-        for res_2 in res_list {
-            if self.does_interfere(res_1, *res_2 as usize) {
-                return true;
-            }
-        }
-        return false;
-    }
-
-    fn set_vehicle_if_not_interfere(&mut self, res_1: usize, veh: usize) -> bool {
-        if !self.does_list_interfere(res_1, &self.veh_to_res[veh]) {
-            if self.unassigned.contains(&(res_1 as i32)) {
-                self.assign_veh_to_res(veh as i32, res_1 as i32);
-                return true;
-            }
-        }
-        return false;
-    }
-
-    fn assign_veh_to_res(&mut self, veh_id: i32, res_id: i32) {
-        self.veh_to_res[veh_id as usize].push(res_id);
-
-        let index = self.unassigned.iter().position(|x| *x == res_id).unwrap();
-        self.unassigned.remove(index);
-    }
-
-    fn assign_zon_to_veh(&mut self, veh_id: i32, zon_id: i32) {
-        self.veh_to_zon[veh_id as usize] = zon_id;
-    }
-
-    fn vehicle_possible_own(&self, veh_id: usize, res_id: usize) -> bool {
-        return self.reservations[res_id].zone == self.veh_to_zon[veh_id]
-            && self.unassigned.contains(&(res_id as i32))
-            && self.reservations[res_id]
-                .possible_vehicles
-                .contains(&(veh_id as i32));
-    }
-
-    fn vehicle_possible_neighbour(&self, veh_id: usize, res_id: usize) -> bool {
-        return self.zones[self.veh_to_zon[veh_id] as usize]
-            .neighbours
-            .contains(&self.reservations[res_id].zone)
-            && self.unassigned.contains(&(res_id as i32))
-            && self.reservations[res_id]
-                .possible_vehicles
-                .contains(&(veh_id as i32));
-    }
-
-    pub fn new(res: Vec<Reservation>, zon: Vec<Zone>, veh: Vec<Vehicle>) -> LocalSearch {
-        let veh_to_zon: Vec<i32> = vec![0; veh.len()];
-        let mut veh_to_res: Vec<Vec<i32>> = vec![];
-
-        let best_veh_to_zon: Vec<i32> = vec![0; veh.len()];
-        let mut best_veh_to_res: Vec<Vec<i32>> = vec![];
-
-        let local_veh_to_zon: Vec<i32> = vec![0; veh.len()];
-        let mut local_veh_to_res: Vec<Vec<i32>> = vec![];
-
-        let mut unassigned: Vec<i32> = vec![];
-        let mut best_unassigned: Vec<i32> = vec![];
-        let mut local_unassigned: Vec<i32> = vec![];
-        for _ in &veh {
-            veh_to_res.push(vec![]);
-            best_veh_to_res.push(vec![]);
-            local_veh_to_res.push(vec![]);
-        }
-        for i in 0..res.len() {
-            unassigned.push(i as i32);
-            best_unassigned.push(i as i32);
-            local_unassigned.push(i as i32);
-        }
-        LocalSearch {
-            reservations: res,
-            zones: zon,
-            vehicle: veh,
-            veh_to_res,
-            veh_to_zon,
-            unassigned,
-            best_cost: 1000000,
-            best_veh_to_res,
-            best_veh_to_zon,
-            best_unassigned,
-            local_cost: 1000000,
-            local_veh_to_res,
-            local_veh_to_zon,
-            local_unassigned,
-        }
-    }
-
-    fn commit(&mut self) {
-        self.local_veh_to_res = self.veh_to_res.clone();
-        self.local_veh_to_zon = self.veh_to_zon.clone();
-        self.local_unassigned = self.unassigned.clone();
-        self.local_cost = self.calculate_full_cost();
-    }
-
-    fn restore(&mut self) {
-        self.veh_to_res = self.local_veh_to_res.clone();
-        self.veh_to_zon = self.local_veh_to_zon.clone();
-        self.unassigned = self.local_unassigned.clone();
-    }
-
-    pub fn run(&mut self, time: i32, seed: u64) {
-        let mut file = File::create("loggin.txt").expect("could not create file");
-
-        self.initialise();
-
-        let mut threshold = 0;
-        let mut age = 1;
-
-        self.commit();
-
-        let mut r = StdRng::seed_from_u64(seed);
-
-        let start_time = Instant::now();
-
-        let mut i = 0;
-
-        while start_time.elapsed().as_secs() < time as u64 {
-            self.local_cost = self.calculate_full_cost();
-
-            for _ in 0..2 {
-                i += 1;
-                let vehicle_id: usize = (r.gen::<u16>() % self.vehicle.len() as u16) as usize;
-                let neighbours = self.zones[self.veh_to_zon[vehicle_id] as usize]
-                    .neighbours
-                    .clone();
-                for zone_id in &neighbours {
-                    self.car_to_zone(vehicle_id as i32, self.zones[*zone_id as usize].id);
-                    let cost = self.calculate_full_cost();
-
-                    if self.check_all() && cost < self.local_cost + threshold {
-                        self.commit();
-                        age = 1;
-                    } else {
-                        self.restore();
-                    }
-                }
-            }
-
-            if self.local_cost != self.best_cost
-                && self.local_cost < self.best_cost + threshold
-                && self.check_all()
-            {
-                if self.local_cost < self.best_cost {
-                    self.best_cost = self.local_cost;
-                    self.best_unassigned = self.local_unassigned.clone();
-                    self.best_veh_to_res = self.local_veh_to_res.clone();
-                    self.best_veh_to_zon = self.local_veh_to_zon.clone();
-                }
-
-                age = 1;
-            } else {
-                age += 1;
-            }
-
-            threshold = 0;
-
-            if age > 10 {
-                threshold = age * 30;
-            }
-        }
-        if self.optimise() {
-            let cost = self.calculate_full_cost();
-            self.commit();
-            if self.check_all() && cost < self.best_cost {
-                self.best_cost = self.local_cost;
-                self.best_unassigned = self.local_unassigned.clone();
-                self.best_veh_to_res = self.local_veh_to_res.clone();
-                self.best_veh_to_zon = self.local_veh_to_zon.clone();
-            }
-        } else {
-            self.restore();
-        }
-
-        println!("\ncost_end: {} after {i} iterations", self.best_cost);
-    }
-
-    fn optimise(&mut self) -> bool {
-        let mut changed = false;
-        for i in 0..self.reservations.len() {
-            self.small_operator(i as i32);
-            let cost = self.calculate_full_cost();
-
-            if cost < self.local_cost {
-                // self.commit();
-                changed = true;
-            }
-            // } else {
-            // self.restore();
-            // }
-        }
-        changed
-    }
-
-    fn check_all(&self) -> bool {
-        let amount_res = self.reservations.len();
-        let mut res = 0;
-        for reservations in &self.veh_to_res {
-            res += reservations.len();
-            for res_1 in reservations {
-                for res_2 in reservations {
-                    if res_1 == res_2 {
-                        continue;
-                    }
-                    if self.does_interfere(*res_1 as usize, *res_2 as usize) {
-                        return false;
-                    }
-                }
-            }
-        }
-        res += self.unassigned.len();
-        if res != amount_res {
-            return false;
-        }
-        true
-    }
-
-    fn small_operator(&mut self, res_id: i32) {
-        // is zone of vehicle zone of reservation?
-        let mut veh_id: usize = 0;
-        let zon_res: i32 = self.reservations[res_id as usize].zone;
-        for (veh, res) in self.veh_to_res.iter().enumerate() {
-            if res.contains(&res_id) {
-                veh_id = veh;
-                break;
-            }
-        }
-        if self.veh_to_zon[veh_id] == zon_res {
-            return;
-        }
-
-        let mut assigned: Vec<i32> = vec![];
-
-        // change vehicle from reservation to possible vehicle in own zone
-        for veh in 0..self.veh_to_zon.len() {
-            // check for vehicle in same zone as res_id
-            if self.veh_to_zon[veh] == zon_res {
-                if self.vehicle_possible_own(veh as usize, res_id as usize) {
-                    if self.set_vehicle_if_not_interfere(res_id as usize, veh) {
-                        if self.unassigned.contains(&res_id) {
-                            assigned.push(res_id);
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    fn reassign(&mut self) {
-        // unnassign all reservations, shuffle all reservations
-        for i in 0..self.veh_to_res.len() {
-            self.veh_to_res[i] = vec![];
-        }
-
-        for res in &self.reservations {
-            self.unassigned.push(res.id);
-        }
-
-        let mut rng = rand::thread_rng();
-
-        self.unassigned.shuffle(&mut rng);
-
-        // reassign
-        let unnassigned_copy = self.unassigned.clone();
-
-        for veh_id in 0..self.vehicle.len() {
-            for res in &unnassigned_copy {
-                if self.vehicle_possible_own(veh_id, *res as usize) {
-                    self.set_vehicle_if_not_interfere(*res as usize, veh_id);
-                }
-            }
-        }
-
-        let unnassigned_copy = self.unassigned.clone();
-
-        for veh_id in 0..self.vehicle.len() {
-            for res in &unnassigned_copy {
-                if self.vehicle_possible_neighbour(veh_id, *res as usize) {
-                    self.set_vehicle_if_not_interfere(*res as usize, veh_id);
-                }
-            }
-        }
-    }
-
-    fn car_to_zone(&mut self, veh_id: i32, zon_id: i32) {
-        if zon_id == self.veh_to_zon[veh_id as usize] {
-            return;
-        }
-
-        self.unassigned
-            .append(&mut self.veh_to_res[veh_id as usize]);
-        self.unassigned.dedup();
-
-        self.veh_to_res[veh_id as usize] = vec![];
-
-        self.veh_to_zon[veh_id as usize] = zon_id;
-
-        // assign all reservations in own zone
-        let unnassigned_copy = self.unassigned.clone();
-
-        for res in unnassigned_copy {
-            if self.vehicle_possible_own(veh_id as usize, res as usize) {
-                self.set_vehicle_if_not_interfere(res as usize, veh_id as usize);
-            }
-        }
-
-        let unnassigned_copy = self.unassigned.clone();
-
-        // assign reservations that were unnassigned from vehicle to other possible vehicle
-        for res in unnassigned_copy {
-            let zone = self.reservations[res as usize].zone;
-            // loop through all vehicles and check if the zone of the reservation is the same as the zone of res
-            for z_index in 0..self.veh_to_zon.len() {
-                if self.veh_to_zon[z_index] == zone {
-                    if self.vehicle_possible_own(z_index as usize, res as usize) {
-                        self.set_vehicle_if_not_interfere(res as usize, z_index);
-                    }
-                }
-            }
-        }
-
-        let unnassigned_copy = self.unassigned.clone();
-
-        // assign all neighbouring reservations to vehicle
-        for res in unnassigned_copy {
-            if self.vehicle_possible_neighbour(veh_id as usize, res as usize) {
-                self.set_vehicle_if_not_interfere(res as usize, veh_id as usize);
-            }
-        }
-
-        for reservations in &mut self.veh_to_res {
-            reservations.dedup();
-        }
-    }
-
-    pub fn initialise(&mut self) {
-        // sort by possible vehicles for reservation
-        self.reservations
-            .sort_by(|a, b| a.possible_vehicles.len().cmp(&b.possible_vehicles.len()));
-
-        let mut used: Vec<i32> = vec![];
-
-        let mut assigned: Vec<i32> = vec![];
-
-        for res_it in 0..self.reservations.len() {
-            for i in 0..self.reservations[res_it].possible_vehicles.len() {
-                let veh_id = self.reservations[res_it].possible_vehicles[i];
-                if !used.contains(&veh_id) {
-                    used.push(veh_id as i32);
-                    self.assign_zon_to_veh(veh_id, self.reservations[res_it].zone);
-                    self.assign_veh_to_res(veh_id, self.reservations[res_it].id);
-                    assigned.push(self.reservations[res_it].id);
-                    break;
-                }
-            }
-        }
-
-        self.reservations.sort_by(|a, b| a.id.cmp(&b.id));
-
-        for veh_id in 0..self.vehicle.len() {
-            for res_id in 0..self.reservations.len() {
-                if assigned.contains(&(res_id as i32)) {
-                    if self.vehicle_possible_own(veh_id, res_id) {
-                        if self.set_vehicle_if_not_interfere(res_id, veh_id) {
-                            assigned.push(res_id as i32);
-                        }
-                    }
-                }
-            }
-        }
-
-        for veh_id in 0..self.vehicle.len() {
-            for res_id in 0..self.reservations.len() {
-                if assigned.contains(&(res_id as i32)) {
-                    if self.vehicle_possible_neighbour(veh_id, res_id) {
-                        if self.set_vehicle_if_not_interfere(res_id, veh_id) {
-                            assigned.push(res_id as i32);
-                        }
-                    }
-                }
-            }
-        }
-
-        for reservations in &mut self.veh_to_res {
-            reservations.dedup();
-        }
-    }
-
-    pub fn calculate_full_cost(&self) -> i32 {
-        let mut sum = 0;
-        for (veh_id, reservations) in self.veh_to_res.iter().enumerate() {
-            for res in reservations {
-                sum += self.calculate_cost(*res, veh_id);
-            }
-            // for res in reservations {
-            //     if self.unassigned.contains(res)
-            //     {
-            //         sum += self.reservations[*res as usize].p1;
-            //     }
-            //     else {
-            //         sum += self.calculate_cost(*res, veh_id);
-            //     }
-            // }
-        }
-        for res in &self.unassigned {
-            sum += self.reservations[*res as usize].p1;
-        }
-        return sum;
-    }
-
-    fn calculate_cost(&self, res_id: i32, veh_id: usize) -> i32 {
-        let zon_veh = self.veh_to_zon.get(veh_id).expect("expected zone");
-        let zon_res = &self.reservations[res_id as usize].zone;
-
-        if *zon_veh == *zon_res {
-            return 0;
-        // } else if self.zones[*zon_veh as usize].neighbours.contains(zon_res) {
-        } else {
-            return self.reservations[res_id as usize].p2;
-        }
-    }
-
-    pub fn write_output(&self, filename: &str) -> std::io::Result<()> {
-        let mut file = File::create(filename)?;
-
-        file.write(format!("{}\n", self.best_cost).as_bytes())?;
-        file.write(b"+Vehicle assignments\n")?;
-        for (i, zone) in self.best_veh_to_zon.iter().enumerate() {
-            file.write(format!("car{};z{}\n", i, zone).as_bytes())?;
-        }
-
-        file.write(b"+Assigned requests\n")?;
-        for (i, veh) in self.best_veh_to_res.iter().enumerate() {
-            for req in veh {
-                file.write(format!("req{};car{}\n", req, i).as_bytes())?;
-            }
-        }
-
-        file.write(b"+Unassigned requests\n")?;
-        for res in &self.best_unassigned {
-            file.write(format!("req{}\n", res).as_bytes())?;
-        }
-        Ok(())
-    }
-}
+use crate::data_structs::{Constraints, Reservation, Solution, Vehicle, Zone};
+use crate::parser;
+use crate::capacity::CapacityMode;
+use crate::checkpoint::{write_checkpoint, Checkpoint};
+use crate::config::Config;
+use crate::ils::RestartStats;
+use crate::reopt::Baseline;
+extern crate rand;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub struct LocalSearch {
+    pub reservations: Vec<Reservation>,
+    pub zones: Vec<Zone>,
+    pub vehicle: Vec<Vehicle>,
+
+    pub veh_to_res: Vec<Vec<i32>>, // given vehicle, get list of reservations that is assigned to it
+    pub veh_to_zon: Vec<i32>,      // given vehicle, get zone that is assigned
+    pub unassigned: Vec<i32>,
+    pub cancelled: Vec<i32>, // reservations withdrawn after the instance was read, kept unassigned at no cost
+
+    pub best_cost: i32,
+    pub best_veh_to_res: Vec<Vec<i32>>,
+    pub best_veh_to_zon: Vec<i32>,
+    pub best_unassigned: Vec<i32>,
+
+    pub local_cost: i32,
+    pub local_veh_to_res: Vec<Vec<i32>>,
+    pub local_veh_to_zon: Vec<i32>,
+    pub local_unassigned: Vec<i32>,
+
+    pub constraints: Constraints,
+    pub capacity_mode: CapacityMode,
+    pub baseline: Option<Baseline>,
+    pub baseline_vehicle: Vec<Option<i32>>, // given reservation, get its car in the baseline
+
+    pub config: Config,
+
+    pub checkpoint_file: Option<String>,
+    pub resume: Option<Checkpoint>,
+    pub stop: Arc<AtomicBool>, // set on SIGINT/SIGTERM, `run` then wraps up with the best so far
+
+    pub anytime_output: Option<String>, // rewritten with every new best
+    pub anytime_format: Option<String>,
+    pub anytime_events: bool, // print a JSON line on stdout for every new best
+
+    pub paranoid: bool, // check every invariant after every move, panic at the first that breaks
+    pub construction_cost: i32, // cost of the starting solution
+    pub elite: Vec<Solution>,   // diverse good solutions met during the search, cheapest first
+    pub restarts: Vec<RestartStats>, // statistics of every restart of the "ils" strategy
+    pub iterations: u64,
+    pub time_to_best: f64, // seconds after the start of the search at which best_cost was found
+}
+
+// touching reservations (one ends when the other starts) also interfere
+pub fn interfere(res_1: &Reservation, res_2: &Reservation) -> bool {
+    // if res_1.day != res_2.day {
+    //     return false;
+    // }
+
+    let start_1 = res_1.start;
+    let end_1 = start_1 + res_1.restime;
+    let start_2 = res_2.start;
+    let end_2 = start_2 + res_2.restime;
+
+    ((start_1 <= start_2) && (start_2 <= end_1))
+        || ((start_1 <= end_2) && (end_2 <= end_1))
+        || ((start_1 <= end_2) && (start_2 <= end_1))
+}
+
+impl LocalSearch {
+    fn does_interfere(&self, res1: usize, res2: usize) -> bool {
+        interfere(&self.reservations[res1], &self.reservations[res2])
+    }
+
+    pub(crate) fn does_list_interfere(&self, res_1: usize, res_list: &Vec<i32>) -> bool {
+        for res_2 in res_list {
+            if self.does_interfere(res_1, *res_2 as usize) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn set_vehicle_if_not_interfere(&mut self, res_1: usize, veh: usize) -> bool {
+        if !self.does_list_interfere(res_1, &self.veh_to_res[veh])
+            && self.unassigned.contains(&(res_1 as i32))
+        {
+            self.assign_veh_to_res(veh as i32, res_1 as i32);
+            return true;
+        }
+        false
+    }
+
+    pub(crate) fn assign_veh_to_res(&mut self, veh_id: i32, res_id: i32) {
+        self.veh_to_res[veh_id as usize].push(res_id);
+
+        let index = self.unassigned.iter().position(|x| *x == res_id).unwrap();
+        self.unassigned.remove(index);
+    }
+
+    pub(crate) fn assign_zon_to_veh(&mut self, veh_id: i32, zon_id: i32) {
+        self.veh_to_zon[veh_id as usize] = zon_id;
+    }
+
+    // veh may serve res wherever it is parked: allowed by the reservation, available during it and
+    // not ruled out by the constraints
+    pub(crate) fn vehicle_possible(&self, veh_id: usize, res_id: usize) -> bool {
+        let res = &self.reservations[res_id];
+        let veh = &self.vehicle[veh_id];
+        res.allows(veh) && veh.available(res) && self.constraints.allows(res, veh_id as i32)
+    }
+
+    pub(crate) fn vehicle_possible_own(&self, veh_id: usize, res_id: usize) -> bool {
+        self.reservations[res_id].zone == self.veh_to_zon[veh_id]
+            && self.unassigned.contains(&(res_id as i32))
+            && self.vehicle_possible(veh_id, res_id)
+    }
+
+    pub(crate) fn vehicle_possible_neighbour(&self, veh_id: usize, res_id: usize) -> bool {
+        self.zones[self.veh_to_zon[veh_id] as usize]
+            .neighbours
+            .contains(&self.reservations[res_id].zone)
+            && self.unassigned.contains(&(res_id as i32))
+            && self.vehicle_possible(veh_id, res_id)
+    }
+
+    pub fn new(res: Vec<Reservation>, zon: Vec<Zone>, veh: Vec<Vehicle>) -> LocalSearch {
+        let veh_to_zon: Vec<i32> = vec![0; veh.len()];
+        let mut veh_to_res: Vec<Vec<i32>> = vec![];
+
+        let best_veh_to_zon: Vec<i32> = vec![0; veh.len()];
+        let mut best_veh_to_res: Vec<Vec<i32>> = vec![];
+
+        let local_veh_to_zon: Vec<i32> = vec![0; veh.len()];
+        let mut local_veh_to_res: Vec<Vec<i32>> = vec![];
+
+        let mut unassigned: Vec<i32> = vec![];
+        let mut best_unassigned: Vec<i32> = vec![];
+        let mut local_unassigned: Vec<i32> = vec![];
+        for _ in &veh {
+            veh_to_res.push(vec![]);
+            best_veh_to_res.push(vec![]);
+            local_veh_to_res.push(vec![]);
+        }
+        for i in 0..res.len() {
+            unassigned.push(i as i32);
+            best_unassigned.push(i as i32);
+            local_unassigned.push(i as i32);
+        }
+        let config = Config::default();
+        LocalSearch {
+            reservations: res,
+            zones: zon,
+            vehicle: veh,
+            veh_to_res,
+            veh_to_zon,
+            unassigned,
+            cancelled: vec![],
+            best_cost: config.initial_cost,
+            best_veh_to_res,
+            best_veh_to_zon,
+            best_unassigned,
+            local_cost: config.initial_cost,
+            local_veh_to_res,
+            local_veh_to_zon,
+            local_unassigned,
+            constraints: Constraints::default(),
+            capacity_mode: CapacityMode::Hard,
+            baseline: None,
+            baseline_vehicle: vec![],
+            config,
+            checkpoint_file: None,
+            resume: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            anytime_output: None,
+            anytime_format: None,
+            anytime_events: false,
+            paranoid: false,
+            construction_cost: 0,
+            elite: vec![],
+            restarts: vec![],
+            iterations: 0,
+            time_to_best: 0.0,
+        }
+    }
+
+    pub(crate) fn commit(&mut self) {
+        self.local_veh_to_res = self.veh_to_res.clone();
+        self.local_veh_to_zon = self.veh_to_zon.clone();
+        self.local_unassigned = self.unassigned.clone();
+        self.local_cost = self.calculate_full_cost();
+    }
+
+    pub(crate) fn restore(&mut self) {
+        self.veh_to_res = self.local_veh_to_res.clone();
+        self.veh_to_zon = self.local_veh_to_zon.clone();
+        self.unassigned = self.local_unassigned.clone();
+    }
+
+    // neither out of time nor iterations, nor asked to stop
+    pub(crate) fn running(&self, start_time: &Instant, time: i32, i: u64) -> bool {
+        start_time.elapsed().as_secs_f64() < time as f64
+            && !self.stop.load(Ordering::Relaxed)
+            && self.config.within_budget(i)
+    }
+
+    pub(crate) fn promote_local_to_best(&mut self) {
+        self.best_cost = self.local_cost;
+        self.best_unassigned = self.local_unassigned.clone();
+        self.best_veh_to_res = self.local_veh_to_res.clone();
+        self.best_veh_to_zon = self.local_veh_to_zon.clone();
+    }
+
+    // the committed state with the cost the search minimises, penalties included
+    pub(crate) fn committed_solution(&self) -> Solution {
+        Solution {
+            cost: self.local_cost,
+            veh_to_zon: self.local_veh_to_zon.clone(),
+            veh_to_res: self.local_veh_to_res.clone(),
+            unassigned: self.local_unassigned.clone(),
+        }
+    }
+
+    // the best state with the cost the search minimises, see best_solution for the one to write out
+    pub(crate) fn best_state(&self) -> Solution {
+        Solution {
+            cost: self.best_cost,
+            veh_to_zon: self.best_veh_to_zon.clone(),
+            veh_to_res: self.best_veh_to_res.clone(),
+            unassigned: self.best_unassigned.clone(),
+        }
+    }
+
+    // make a solution the live and committed state
+    pub(crate) fn load_state(&mut self, solution: &Solution) {
+        self.veh_to_res = solution.veh_to_res.clone();
+        self.veh_to_zon = solution.veh_to_zon.clone();
+        self.unassigned = solution.unassigned.clone();
+        self.commit();
+    }
+
+    // neither frozen by the baseline nor fixed to its zone by the constraints
+    pub(crate) fn movable(&self, veh: usize) -> bool {
+        !self.is_frozen_vehicle(veh) && self.constraints.fixed_zone(veh as i32).is_none()
+    }
+
+    // a car of the zone vehicle_id just entered that may go to the zone it left instead
+    pub(crate) fn swap_partner(&self, vehicle_id: usize, zone_id: i32, old_zone: i32) -> Option<usize> {
+        (0..self.vehicle.len()).find(|veh| {
+            *veh != vehicle_id
+                && self.veh_to_zon[*veh] == zone_id
+                && self.movable(*veh)
+                && !self.strands_tied(*veh, old_zone)
+        })
+    }
+
+    // the zone move every search makes: false, with nothing moved, for a car that may not move or a
+    // zone out of reach of its tied reservations; a full zone is entered by sending one of its cars
+    // the other way
+    pub(crate) fn move_car(&mut self, vehicle_id: usize, zone_id: i32) -> bool {
+        let old_zone = self.veh_to_zon[vehicle_id];
+        if old_zone == zone_id || !self.movable(vehicle_id) || self.strands_tied(vehicle_id, zone_id) {
+            return false;
+        }
+        self.car_to_zone(vehicle_id as i32, zone_id);
+
+        if self.capacity_mode == CapacityMode::Hard && !self.capacity_holds() {
+            if let Some(other) = self.swap_partner(vehicle_id, zone_id, old_zone) {
+                self.car_to_zone(other as i32, old_zone);
+                self.paranoid_check(|| format!("swap of car{} into z{} with car{}", vehicle_id, zone_id, other));
+                if self.config.exact_repair {
+                    self.schedule_car(other);
+                }
+            }
+        }
+        if self.config.exact_repair {
+            self.schedule_car(vehicle_id);
+        }
+        self.paranoid_check(|| format!("car_to_zone(car{}, z{}) from z{}", vehicle_id, zone_id, old_zone));
+        true
+    }
+
+    // move the vehicle to each neighbouring zone in turn, keeping every move within the threshold
+    pub(crate) fn try_neighbour_zones(&mut self, vehicle_id: usize, threshold: i32) -> bool {
+        let mut accepted = false;
+        let neighbours = self.zones[self.veh_to_zon[vehicle_id] as usize]
+            .neighbours
+            .clone();
+        for zone_id in &neighbours {
+            if !self.move_car(vehicle_id, *zone_id) {
+                continue;
+            }
+            let cost = self.calculate_full_cost();
+
+            if self.check_all() && cost < self.local_cost + threshold {
+                self.commit();
+                accepted = true;
+            } else {
+                self.restore();
+            }
+        }
+        accepted
+    }
+
+    // starting solution of every search
+    pub(crate) fn construct(&mut self, seed: u64) {
+        if self.baseline.is_some() {
+            self.load_baseline();
+        } else {
+            match self.config.constructor.as_str() {
+                "greedy" => self.initialise(),
+                "demand" => self.construct_demand(),
+                "regret" => self.construct_regret(),
+                "grasp" => self.construct_grasp(seed),
+                "matching" => self.construct_matching(),
+                constructor => panic!("unknown constructor {}", constructor),
+            }
+        }
+        self.construction_cost = self.calculate_full_cost();
+        self.paranoid_check(|| format!("construct({}, seed {})", self.config.constructor, seed));
+    }
+
+    pub fn run(&mut self, time: i32, seed: u64) {
+        // a resumed search carries on with the random stream it saved, whatever seed it is given
+        let (seed, mut threshold, mut age, mut i, offset, mut r) = match self.resume.take() {
+            Some(checkpoint) => {
+                self.load_checkpoint(&checkpoint);
+                (
+                    checkpoint.seed,
+                    checkpoint.threshold,
+                    checkpoint.age,
+                    checkpoint.iterations,
+                    checkpoint.elapsed,
+                    checkpoint.rng(),
+                )
+            }
+            None => {
+                self.construct(seed);
+                self.commit();
+                (seed, 0, 1, 0, 0.0, ChaCha12Rng::seed_from_u64(seed))
+            }
+        };
+
+        let start_time = Instant::now();
+        let elapsed = |start_time: &Instant| offset + start_time.elapsed().as_secs_f64();
+
+        let mut last_checkpoint = offset;
+
+        while elapsed(&start_time) < time as f64
+            && !self.stop.load(Ordering::Relaxed)
+            && self.config.within_budget(i)
+        {
+            self.local_cost = self.calculate_full_cost();
+
+            for _ in 0..self.config.moves_per_iteration {
+                i += 1;
+                let vehicle_id: usize = (r.gen::<u16>() % self.vehicle.len() as u16) as usize;
+                if self.try_neighbour_zones(vehicle_id, threshold) {
+                    age = 1;
+                }
+            }
+            self.offer_elite();
+
+            if self.local_cost != self.best_cost
+                && self.local_cost < self.best_cost + threshold
+                && self.check_all()
+            {
+                if self.local_cost < self.best_cost {
+                    self.promote_local_to_best();
+                    self.time_to_best = elapsed(&start_time);
+                    self.improved(self.time_to_best, i);
+                    self.paranoid_check(|| format!("new best at iteration {}", i));
+                }
+
+                age = 1;
+            } else {
+                age += 1;
+            }
+
+            threshold = 0;
+
+            if age > self.config.stagnation_limit {
+                threshold = age * self.config.threshold_step;
+            }
+
+            if let Some(filename) = &self.checkpoint_file {
+                if elapsed(&start_time) - last_checkpoint >= self.config.checkpoint_interval {
+                    last_checkpoint = elapsed(&start_time);
+                    let checkpoint = self.checkpoint(seed, &r, threshold, age, i, last_checkpoint);
+                    let _ = write_checkpoint(filename, &checkpoint);
+                }
+            }
+        }
+        // the time already spent before a resume counts towards the limit
+        let deadline = start_time + Duration::from_secs_f64((time as f64 - offset).max(0.0));
+        if self.relink_elite(deadline) {
+            self.time_to_best = elapsed(&start_time);
+            self.improved(self.time_to_best, i);
+        }
+        if self.polish_best() {
+            self.time_to_best = elapsed(&start_time);
+            self.improved(self.time_to_best, i);
+        }
+
+        self.iterations = i;
+
+        if let Some(filename) = &self.checkpoint_file {
+            let checkpoint = self.checkpoint(seed, &r, threshold, age, i, elapsed(&start_time));
+            let _ = write_checkpoint(filename, &checkpoint);
+        }
+
+        self.log(format!("\ncost_end: {} after {i} iterations", self.best_cost));
+    }
+
+    // load the best solution and run the configured polish on it, true if that improved it
+    pub(crate) fn polish_best(&mut self) -> bool {
+        self.load_state(&self.best_state());
+        let polished = match self.config.polish.as_str() {
+            "optimise" => self.optimise(),
+            "schedule" => self.schedule_all(),
+            polish => panic!("unknown polish {}", polish),
+        };
+        if !polished {
+            self.restore();
+            return false;
+        }
+        let cost = self.calculate_full_cost();
+        self.commit();
+        if self.check_all() && cost < self.best_cost {
+            self.promote_local_to_best();
+            return true;
+        }
+        false
+    }
+
+    // post-optimisation: reservations served from a neighbouring zone move to a free car in their
+    // own zone, then unassigned reservations that fit somewhere now are inserted, until neither
+    // helps; every step lowers the cost, so the pass never makes a solution worse
+    pub(crate) fn optimise(&mut self) -> bool {
+        let mut changed = false;
+        loop {
+            let mut improved = false;
+            for res_id in 0..self.reservations.len() {
+                improved |= self.small_operator(res_id as i32);
+                self.paranoid_check(|| format!("small_operator(req{})", res_id));
+            }
+            for res_id in self.unassigned.clone() {
+                improved |= self.insert_unassigned(res_id);
+                self.paranoid_check(|| format!("insert_unassigned(req{})", res_id));
+            }
+            if !improved {
+                return changed;
+            }
+            changed = true;
+        }
+    }
+
+    pub(crate) fn check_all(&self) -> bool {
+        let amount_res = self.reservations.len();
+        let mut res = 0;
+        for (veh_id, reservations) in self.veh_to_res.iter().enumerate() {
+            res += reservations.len();
+            let zone = &self.zones[self.veh_to_zon[veh_id] as usize];
+            for res_1 in reservations {
+                let zon_res = self.reservations[*res_1 as usize].zone;
+                if zon_res != zone.id && !zone.neighbours.contains(&zon_res) {
+                    return false;
+                }
+                for res_2 in reservations {
+                    if res_1 == res_2 {
+                        continue;
+                    }
+                    if self.does_interfere(*res_1 as usize, *res_2 as usize) {
+                        return false;
+                    }
+                }
+            }
+        }
+        res += self.unassigned.len();
+        if res != amount_res {
+            return false;
+        }
+        self.capacity_holds()
+            && self
+                .constraints
+                .check(&self.reservations, &self.veh_to_res, &self.veh_to_zon)
+                .is_ok()
+    }
+
+    // move res_id from a car in a neighbouring zone to the best car in its own zone that is free at
+    // that time, true if that lowered the cost
+    fn small_operator(&mut self, res_id: i32) -> bool {
+        let Some(veh_id) = self.veh_to_res.iter().position(|res| res.contains(&res_id)) else {
+            return false;
+        };
+        let zon_res = self.reservations[res_id as usize].zone;
+        if self.veh_to_zon[veh_id] == zon_res || self.tied_to(res_id).is_some() {
+            return false;
+        }
+
+        let target = (0..self.vehicle.len())
+            .filter(|veh| {
+                self.veh_to_zon[*veh] == zon_res
+                    && self.vehicle_possible(*veh, res_id as usize)
+                    && !self.does_list_interfere(res_id as usize, &self.veh_to_res[*veh])
+            })
+            .max_by_key(|veh| self.gain(res_id, *veh));
+        match target {
+            Some(veh) if self.gain(res_id, veh) > self.gain(res_id, veh_id) => {
+                self.veh_to_res[veh_id].retain(|res| *res != res_id);
+                self.veh_to_res[veh].push(res_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // serve an unassigned reservation with the car that gains most, true if there was one
+    fn insert_unassigned(&mut self, res_id: i32) -> bool {
+        let target = (0..self.vehicle.len())
+            .filter(|veh| self.can_serve(*veh, res_id) && !self.does_list_interfere(res_id as usize, &self.veh_to_res[*veh]))
+            .max_by_key(|veh| self.gain(res_id, *veh));
+        match target {
+            Some(veh) if self.gain(res_id, veh) > 0 => {
+                self.assign_veh_to_res(veh as i32, res_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[allow(dead_code)]
+    fn reassign(&mut self) {
+        // unnassign all reservations, shuffle all reservations
+        for i in 0..self.veh_to_res.len() {
+            self.veh_to_res[i] = vec![];
+        }
+
+        for res in &self.reservations {
+            self.unassigned.push(res.id);
+        }
+
+        let mut rng = rand::thread_rng();
+
+        self.unassigned.shuffle(&mut rng);
+
+        // reassign
+        let unnassigned_copy = self.unassigned.clone();
+
+        for veh_id in 0..self.vehicle.len() {
+            for res in &unnassigned_copy {
+                if self.vehicle_possible_own(veh_id, *res as usize) {
+                    self.set_vehicle_if_not_interfere(*res as usize, veh_id);
+                }
+            }
+        }
+
+        let unnassigned_copy = self.unassigned.clone();
+
+        for veh_id in 0..self.vehicle.len() {
+            for res in &unnassigned_copy {
+                if self.vehicle_possible_neighbour(veh_id, *res as usize) {
+                    self.set_vehicle_if_not_interfere(*res as usize, veh_id);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn car_to_zone(&mut self, veh_id: i32, zon_id: i32) {
+        if zon_id == self.veh_to_zon[veh_id as usize] || self.constraints.fixed_zone(veh_id).is_some() {
+            return;
+        }
+
+        self.unassigned
+            .append(&mut self.veh_to_res[veh_id as usize]);
+        self.unassigned.dedup();
+
+        self.veh_to_res[veh_id as usize] = vec![];
+
+        self.veh_to_zon[veh_id as usize] = zon_id;
+
+        // assign all reservations in own zone
+        let unnassigned_copy = self.unassigned.clone();
+
+        for res in unnassigned_copy {
+            if self.vehicle_possible_own(veh_id as usize, res as usize) {
+                self.set_vehicle_if_not_interfere(res as usize, veh_id as usize);
+            }
+        }
+
+        let unnassigned_copy = self.unassigned.clone();
+
+        // assign reservations that were unnassigned from vehicle to other possible vehicle
+        for res in unnassigned_copy {
+            let zone = self.reservations[res as usize].zone;
+            // loop through all vehicles and check if the zone of the reservation is the same as the zone of res
+            for z_index in 0..self.veh_to_zon.len() {
+                if self.veh_to_zon[z_index] == zone
+                    && self.vehicle_possible_own(z_index, res as usize)
+                {
+                    self.set_vehicle_if_not_interfere(res as usize, z_index);
+                }
+            }
+        }
+
+        let unnassigned_copy = self.unassigned.clone();
+
+        // assign all neighbouring reservations to vehicle
+        for res in unnassigned_copy {
+            if self.vehicle_possible_neighbour(veh_id as usize, res as usize) {
+                self.set_vehicle_if_not_interfere(res as usize, veh_id as usize);
+            }
+        }
+
+        for reservations in &mut self.veh_to_res {
+            reservations.dedup();
+        }
+    }
+
+    // cars with a home zone, a fixed zone and pinned reservations go first, returns the cars placed
+    pub(crate) fn place_fixed_cars(&mut self) -> Vec<i32> {
+        let mut used: Vec<i32> = vec![];
+
+        for veh_id in 0..self.vehicle.len() {
+            if let Some(zon_id) = self.vehicle[veh_id].zone {
+                used.push(veh_id as i32);
+                self.assign_zon_to_veh(veh_id as i32, zon_id);
+            }
+        }
+        for (veh_id, zon_id) in self.constraints.fixed_zones.clone() {
+            if !used.contains(&veh_id) {
+                used.push(veh_id);
+            }
+            self.assign_zon_to_veh(veh_id, zon_id);
+        }
+        for (res_id, veh_id) in self.constraints.pinned.clone() {
+            if !used.contains(&veh_id) {
+                used.push(veh_id);
+                let zone = self.pinned_zone(veh_id).unwrap_or(self.reservations[res_id as usize].zone);
+                self.assign_zon_to_veh(veh_id, zone);
+            }
+            if self.vehicle_possible_own(veh_id as usize, res_id as usize)
+                || self.vehicle_possible_neighbour(veh_id as usize, res_id as usize)
+            {
+                self.set_vehicle_if_not_interfere(res_id as usize, veh_id as usize);
+            }
+        }
+        used
+    }
+
+    pub fn initialise(&mut self) {
+        let mut used = self.place_fixed_cars();
+
+        // sort by possible vehicles for reservation, the listed ones first in their order, then the
+        // other cars of an allowed category
+        let possible: Vec<Vec<i32>> = self
+            .reservations
+            .iter()
+            .map(|res| {
+                let listed = res.possible_vehicles.iter().copied();
+                let others = (0..self.vehicle.len() as i32).filter(|veh_id| !res.possible_vehicles.contains(veh_id));
+                listed
+                    .chain(others)
+                    .filter(|veh_id| self.vehicle_possible(*veh_id as usize, res.id as usize))
+                    .collect()
+            })
+            .collect();
+        let mut order: Vec<usize> = (0..self.reservations.len()).collect();
+        order.sort_by_key(|res_id| possible[*res_id].len());
+
+        let mut assigned: Vec<i32> = vec![];
+
+        for res_id in order {
+            for veh_id in &possible[res_id] {
+                if !used.contains(veh_id) {
+                    used.push(*veh_id);
+                    self.assign_zon_to_veh(*veh_id, self.reservations[res_id].zone);
+                    self.assign_veh_to_res(*veh_id, res_id as i32);
+                    assigned.push(res_id as i32);
+                    break;
+                }
+            }
+        }
+
+        for veh_id in 0..self.vehicle.len() {
+            for res_id in 0..self.reservations.len() {
+                if assigned.contains(&(res_id as i32))
+                    && self.vehicle_possible_own(veh_id, res_id)
+                    && self.set_vehicle_if_not_interfere(res_id, veh_id)
+                {
+                    assigned.push(res_id as i32);
+                }
+            }
+        }
+
+        for veh_id in 0..self.vehicle.len() {
+            for res_id in 0..self.reservations.len() {
+                if assigned.contains(&(res_id as i32))
+                    && self.vehicle_possible_neighbour(veh_id, res_id)
+                    && self.set_vehicle_if_not_interfere(res_id, veh_id)
+                {
+                    assigned.push(res_id as i32);
+                }
+            }
+        }
+
+        for reservations in &mut self.veh_to_res {
+            reservations.dedup();
+        }
+
+        self.spread_over_capacity(&used);
+    }
+
+    pub fn calculate_full_cost(&self) -> i32 {
+        let mut sum = 0;
+        for (veh_id, reservations) in self.veh_to_res.iter().enumerate() {
+            for res in reservations {
+                sum += self.calculate_cost(*res, veh_id);
+            }
+            // for res in reservations {
+            //     if self.unassigned.contains(res)
+            //     {
+            //         sum += self.reservations[*res as usize].p1;
+            //     }
+            //     else {
+            //         sum += self.calculate_cost(*res, veh_id);
+            //     }
+            // }
+        }
+        for res in &self.unassigned {
+            sum += self.reservations[*res as usize].p1;
+        }
+        sum + self.penalty(&self.veh_to_res, &self.veh_to_zon, &self.unassigned)
+    }
+
+    // soft terms of the objective on top of p1/p2, never part of the written cost
+    pub(crate) fn penalty(&self, veh_to_res: &[Vec<i32>], veh_to_zon: &[i32], unassigned: &[i32]) -> i32 {
+        self.disruption(veh_to_res, veh_to_zon, unassigned) + self.capacity_penalty(veh_to_zon)
+    }
+
+    pub(crate) fn calculate_cost(&self, res_id: i32, veh_id: usize) -> i32 {
+        let zon_veh = self.veh_to_zon.get(veh_id).expect("expected zone");
+        let zon_res = &self.reservations[res_id as usize].zone;
+
+        let upgrade = self.reservations[res_id as usize].upgrade_penalty(&self.vehicle[veh_id]);
+
+        if *zon_veh == *zon_res {
+            upgrade
+        // } else if self.zones[*zon_veh as usize].neighbours.contains(zon_res) {
+        } else {
+            self.reservations[res_id as usize].p2 + upgrade
+        }
+    }
+
+    pub fn current_solution(&self) -> Solution {
+        Solution {
+            cost: self.calculate_full_cost()
+                - self.penalty(&self.veh_to_res, &self.veh_to_zon, &self.unassigned),
+            veh_to_zon: self.veh_to_zon.clone(),
+            veh_to_res: self.veh_to_res.clone(),
+            unassigned: self.unassigned.clone(),
+        }
+    }
+
+    pub fn best_solution(&self) -> Solution {
+        let mut solution = self.best_state();
+        solution.cost -= self.penalty(&solution.veh_to_res, &solution.veh_to_zon, &solution.unassigned);
+        solution
+    }
+
+    pub fn write_output(&self, filename: &str) -> std::io::Result<()> {
+        parser::write_solution(filename, &self.best_solution())
+    }
+
+    pub fn write_output_json(&self, filename: &str) -> std::io::Result<()> {
+        parser::write_solution_json(filename, &self.best_solution())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::validate;
+
+    // start is given within its day, as in the input files
+    fn reservation(day: i32, start: i32, restime: i32) -> Reservation {
+        Reservation {
+            id: 0,
+            zone: 0,
+            day,
+            start: start + 1440 * day,
+            restime,
+            possible_vehicles: vec![0],
+            categories: vec![],
+            p1: 100,
+            p2: 20,
+            priority: 1,
+            vehicle: None,
+        }
+    }
+
+    #[test]
+    fn touching_reservations_interfere() {
+        let a = reservation(0, 600, 60);
+        let b = reservation(0, 660, 30);
+        assert!(interfere(&a, &b));
+        assert!(interfere(&b, &a));
+    }
+
+    #[test]
+    fn separate_reservations_do_not_interfere() {
+        let a = reservation(0, 600, 60);
+        let b = reservation(0, 661, 30);
+        assert!(!interfere(&a, &b));
+        assert!(!interfere(&b, &a));
+    }
+
+    #[test]
+    fn nested_and_equal_reservations_interfere() {
+        let a = reservation(0, 600, 240);
+        let b = reservation(0, 700, 30);
+        assert!(interfere(&a, &b));
+        assert!(interfere(&b, &a));
+        assert!(interfere(&a, &a));
+    }
+
+    #[test]
+    fn reservation_running_past_midnight_interferes_with_next_day() {
+        let a = reservation(0, 1400, 120);
+        let b = reservation(1, 30, 60);
+        assert!(interfere(&a, &b));
+        assert!(interfere(&b, &a));
+        assert!(!interfere(&a, &reservation(1, 81, 60)));
+    }
+
+    #[test]
+    fn same_hours_on_different_days_do_not_interfere() {
+        let a = reservation(0, 600, 60);
+        let b = reservation(1, 600, 60);
+        assert!(!interfere(&a, &b));
+
+        let vehicle = Vehicle {
+            id: 0,
+            zone: None,
+            availability: vec![],
+            category: None,
+        };
+        let ls = LocalSearch::new(vec![a, Reservation { id: 1, ..b }], vec![], vec![vehicle]);
+        assert!(!ls.does_interfere(0, 1));
+    }
+
+    #[test]
+    fn categories_are_checked_without_listing_their_cars() {
+        let res = Reservation {
+            possible_vehicles: vec![],
+            categories: vec![("van".to_string(), 10)],
+            ..reservation(0, 600, 60)
+        };
+        let car = |id: i32, category: &str, availability: Vec<(i32, i32)>| Vehicle {
+            id,
+            zone: None,
+            availability,
+            category: Some(category.to_string()),
+        };
+        let vehicles = vec![car(0, "compact", vec![]), car(1, "van", vec![]), car(2, "van", vec![(0, 500)])];
+        let zone = Zone {
+            id: 0,
+            neighbours: vec![],
+            capacity: None,
+        };
+        let ls = LocalSearch::new(vec![res], vec![zone], vehicles);
+
+        assert!(ls.reservations[0].possible_vehicles.is_empty());
+        assert!(!ls.vehicle_possible(0, 0));
+        assert!(ls.vehicle_possible(1, 0));
+        assert!(!ls.vehicle_possible(2, 0));
+    }
+
+    // starting points with work to do: a random construction with some cars moved away afterwards
+    #[test]
+    fn optimise_never_increases_cost() {
+        let mut improved = false;
+        for file in ["input/toy1.csv", "input/100_5_14_25.csv", "input/360_5_71_25.csv"] {
+            for seed in 0..5 {
+                let (res, zone, veh) = parser::read_file(file);
+                let mut ls = LocalSearch::new(res, zone, veh);
+                ls.config.grasp_alpha = 1.0;
+                ls.construct_grasp(seed);
+                let mut r = ChaCha12Rng::seed_from_u64(seed);
+                for _ in 0..5 {
+                    let veh = r.gen_range(0..ls.vehicle.len());
+                    let zone = r.gen_range(0..ls.zones.len()) as i32;
+                    ls.car_to_zone(veh as i32, zone);
+                }
+
+                let before = ls.calculate_full_cost();
+                improved |= ls.optimise();
+                let after = ls.calculate_full_cost();
+                assert!(after <= before, "{} seed {}: {} -> {}", file, seed, before, after);
+                assert!(ls.check_all());
+                let solution = ls.current_solution();
+                assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution), Ok(solution.cost));
+            }
+        }
+        assert!(improved);
+    }
+}
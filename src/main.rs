@@ -1,18 +1,289 @@
-pub mod data_structs;
-pub mod parser;
-pub mod ls;
-
-fn main() {
-
-    let input_filename = std::env::args().nth(1).expect("No input file given...");
-    let output_filename = std::env::args().nth(2).expect("No output file given...");
-    let time = std::env::args().nth(3).expect("No time has been given...");
-    let seed = std::env::args().nth(4).expect("No seed had been given...");
-
-    let (res, zone, veh) = parser::read_file(&input_filename);
-
-    let mut ls = ls::LocalSearch::new(res, zone, veh);
-    ls.run(time.parse::<i32>().expect("No number given as time"), seed.parse::<u64>().expect("No number given as seed"));
-
-    let _ = ls.write_output(&output_filename);
-}
\ No newline at end of file
+pub mod data_structs;
+pub mod parser;
+pub mod ls;
+pub mod validator;
+pub mod bench;
+pub mod report;
+pub mod explain;
+pub mod online;
+pub mod reopt;
+pub mod constraints;
+pub mod capacity;
+pub mod objectives;
+pub mod fleet;
+pub mod checkpoint;
+pub mod anytime;
+pub mod config;
+pub mod tune;
+pub mod construct;
+pub mod schedule;
+pub mod flow;
+pub mod invariants;
+pub mod diff;
+pub mod elite;
+pub mod memetic;
+pub mod ils;
+#[cfg(test)]
+mod tests;
+
+fn parse_list<T: std::str::FromStr>(list: &str) -> Vec<T>
+where
+    T::Err: std::fmt::Debug,
+{
+    list.split(',')
+        .map(|x| x.parse::<T>().expect("Not a number in list"))
+        .collect()
+}
+
+// value following a `--name` flag anywhere on the command line
+fn flag(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == name)?;
+    args.get(index + 1).cloned()
+}
+
+fn main() {
+
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        let dir = std::env::args().nth(2).expect("No input directory given...");
+        let output_filename = std::env::args().nth(3).expect("No output file given...");
+        let times = std::env::args().nth(4).expect("No times have been given...");
+        let seeds = std::env::args().nth(5).expect("No seeds have been given...");
+
+        let config = match flag("--config") {
+            Some(filename) => config::read_config(&filename),
+            None => config::Config::preset(flag("--preset").as_deref().unwrap_or("balanced")),
+        };
+        let configs: Vec<config::Config> = match flag("--constructors") {
+            Some(list) => list
+                .split(',')
+                .map(|constructor| config::Config {
+                    constructor: constructor.to_string(),
+                    ..config.clone()
+                })
+                .collect(),
+            None => vec![config],
+        };
+        bench::bench(&dir, &output_filename, &parse_list::<i32>(&times), &parse_list::<u64>(&seeds), &configs)
+            .expect("could not write benchmark results");
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("tune") {
+        let dir = std::env::args().nth(2).expect("No training directory given...");
+        let output_filename = std::env::args().nth(3).expect("No output config given...");
+        let budget = std::env::args().nth(4).expect("No budget (number of runs) given...");
+        let time = std::env::args().nth(5).expect("No time per run given...");
+
+        let base = match flag("--config") {
+            Some(filename) => config::read_config(&filename),
+            None => config::Config::preset(flag("--preset").as_deref().unwrap_or("balanced")),
+        };
+        let ranges = match flag("--ranges").map_or_else(|| Ok(tune::default_ranges()), |filename| tune::read_ranges(&filename)) {
+            Ok(ranges) => ranges,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        let result = tune::tune(
+            &dir,
+            &output_filename,
+            budget.parse().expect("No number given as budget"),
+            time.parse().expect("No number given as time"),
+            base,
+            ranges,
+            flag("--seed").map_or(0, |seed| seed.parse().expect("No number given as seed")),
+        );
+        match result {
+            Ok(best) => println!("config: {}", best.to_json()),
+            Err(e) => {
+                eprintln!("could not tune: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("convert") {
+        let kind = std::env::args().nth(2).expect("No kind (instance or solution) given...");
+        let input_filename = std::env::args().nth(3).expect("No input file given...");
+        let output_filename = std::env::args().nth(4).expect("No output file given...");
+
+        if kind == "solution" {
+            let solution = parser::load_solution(&input_filename, None);
+            parser::save_solution(&output_filename, &solution, None).expect("could not write solution");
+        } else {
+            let (res, zone, veh) = parser::load_instance(&input_filename, None);
+            if parser::is_json(&output_filename, None) {
+                parser::write_json(&output_filename, res, zone, veh).expect("could not write instance");
+            } else {
+                parser::write_file(&output_filename, &res, &zone, &veh).expect("could not write instance");
+            }
+        }
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("report") {
+        let input_filename = std::env::args().nth(2).expect("No input file given...");
+        let solution_filename = std::env::args().nth(3).expect("No solution file given...");
+
+        let (res, zone, veh) = parser::load_instance(&input_filename, flag("--format").as_deref());
+        let solution = parser::load_solution(&solution_filename, None);
+
+        report::write_report(&mut std::io::stdout(), &res, &zone, &veh, &solution).expect("could not print report");
+        if let Some(html) = flag("--html") {
+            report::write_html(&html, &res, &zone, &veh, &solution).expect("could not write report");
+        }
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("diff") {
+        let input_filename = std::env::args().nth(2).expect("No input file given...");
+        let old_filename = std::env::args().nth(3).expect("No old solution file given...");
+        let new_filename = std::env::args().nth(4).expect("No new solution file given...");
+
+        let (res, _, veh) = parser::load_instance(&input_filename, flag("--format").as_deref());
+        let old = parser::load_solution(&old_filename, None);
+        let new = parser::load_solution(&new_filename, None);
+
+        let changes = match diff::diff(&res, &veh, &old, &new) {
+            Ok(changes) => changes,
+            Err(e) => {
+                eprintln!("can not compare the solutions: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if std::env::args().any(|arg| arg == "--json") {
+            println!("{}", changes.to_json());
+        } else {
+            print!("{}", changes);
+        }
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("validate") {
+        let input_filename = std::env::args().nth(2).expect("No input file given...");
+        let solution_filename = std::env::args().nth(3).expect("No solution file given...");
+
+        let (res, zone, veh) = parser::load_instance(&input_filename, flag("--format").as_deref());
+        let solution = parser::load_solution(&solution_filename, None);
+
+        let mut result = validator::validate(&res, &zone, &veh, &solution).map(|_| ());
+        if let Some(constraints) = flag("--constraints") {
+            result = result.and_then(|_| validator::validate_constraints(&res, &solution, &parser::read_constraints(&constraints)));
+        }
+        match result {
+            Ok(()) => println!("solution is feasible, cost {}", solution.cost),
+            Err(e) => {
+                println!("solution is not feasible: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let input_filename = std::env::args().nth(1).expect("No input file given...");
+    let output_filename = std::env::args().nth(2).expect("No output file given...");
+    let time = std::env::args().nth(3).expect("No time has been given...");
+    let seed = std::env::args().nth(4).expect("No seed had been given...");
+
+    let format = flag("--format");
+    let output_format = flag("--output-format");
+
+    let (res, zone, veh) = parser::load_instance(&input_filename, format.as_deref());
+
+    let mut config = match flag("--config") {
+        Some(filename) => config::read_config(&filename),
+        None => config::Config::preset(flag("--preset").as_deref().unwrap_or("balanced")),
+    };
+    if let Some(interval) = flag("--checkpoint-interval") {
+        config.checkpoint_interval = interval.parse().expect("No number given as checkpoint interval");
+    }
+    if let Some(iterations) = flag("--iterations") {
+        config.max_iterations = Some(iterations.parse().expect("No number given as iterations"));
+    }
+    if let Some(strategy) = flag("--strategy") {
+        config.strategy = strategy;
+    }
+    if let Some(constructor) = flag("--constructor") {
+        config.constructor = constructor;
+    }
+    if let Some(spec) = flag("--objectives") {
+        config.objectives = Some(spec);
+    }
+    // with --events stdout carries nothing but the events
+    let events = std::env::args().any(|arg| arg == "--events");
+    let log = |line: String| if events { eprintln!("{}", line) } else { println!("{}", line) };
+    log(format!("config: {}", config.to_json()));
+
+    let mut ls = ls::LocalSearch::new(res, zone, veh);
+    ls.set_config(config.clone());
+    if let Some(penalty) = flag("--capacity-penalty") {
+        ls.capacity_mode = capacity::CapacityMode::Soft(penalty.parse().expect("No number given as capacity penalty"));
+    }
+    if let Some(constraints) = flag("--constraints") {
+        if let Err(e) = ls.set_constraints(parser::read_constraints(&constraints)) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = ls.held_cars_fit() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if let Some(baseline) = flag("--baseline") {
+        ls.set_baseline(reopt::Baseline {
+            solution: parser::load_solution(&baseline, None),
+            zone_penalty: flag("--zone-penalty").map_or(0, |p| p.parse().expect("No number given as zone penalty")),
+            move_penalty: flag("--move-penalty").map_or(0, |p| p.parse().expect("No number given as move penalty")),
+            frozen: flag("--frozen").map_or(vec![], |list| parse_list::<i32>(&list)),
+        });
+    }
+    if (flag("--resume").is_some() || flag("--checkpoint").is_some()) && !config.checkpoints() {
+        eprintln!("--checkpoint and --resume need the threshold strategy without --objectives");
+        std::process::exit(1);
+    }
+    if let Some(filename) = flag("--resume") {
+        ls.resume = Some(checkpoint::read_checkpoint(&filename));
+        ls.checkpoint_file = Some(filename);
+    }
+    if let Some(filename) = flag("--checkpoint") {
+        ls.checkpoint_file = Some(filename);
+    }
+    if std::env::args().any(|arg| arg == "--anytime") {
+        ls.anytime_output = Some(output_filename.clone());
+        ls.anytime_format = output_format.clone();
+    }
+    ls.anytime_events = events;
+    ls.paranoid = std::env::args().any(|arg| arg == "--paranoid");
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        signal_hook::flag::register(signal, ls.stop.clone()).expect("could not register signal handler");
+    }
+
+    let time = time.parse::<i32>().expect("No number given as time");
+    let seed = seed.parse::<u64>().expect("No number given as seed");
+    match config.objectives.as_deref() {
+        Some("pareto") => {
+            for (k, (objectives, solution)) in ls.run_pareto(time, seed).iter().enumerate() {
+                log(format!("{}: {:?}", k, objectives));
+                let _ = parser::save_solution(&format!("{}.{}", output_filename, k), solution, output_format.as_deref());
+            }
+        }
+        _ => ls.solve(time, seed),
+    }
+
+    if parser::is_json(&output_filename, output_format.as_deref()) {
+        let _ = ls.write_output_json(&output_filename);
+    } else {
+        let _ = ls.write_output(&output_filename);
+    }
+
+    if std::env::args().any(|arg| arg == "--report") {
+        let mut out: Box<dyn std::io::Write> = if events { Box::new(std::io::stderr()) } else { Box::new(std::io::stdout()) };
+        let _ = report::write_report(&mut out, &ls.reservations, &ls.zones, &ls.vehicle, &ls.best_solution());
+    }
+    if let Some(html) = flag("--html") {
+        let _ = report::write_html(&html, &ls.reservations, &ls.zones, &ls.vehicle, &ls.best_solution());
+    }
+}
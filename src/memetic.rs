@@ -0,0 +1,186 @@
+use crate::data_structs::Solution;
+use crate::elite::{admission, admit};
+use crate::ls::LocalSearch;
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::time::{Duration, Instant};
+
+// share of the cars moved when the starting population is spread out from the constructed solution
+const INITIAL_MUTATION_RATE: f64 = 0.5;
+
+// memetic search: the chromosome is the zone layout, the reservations follow from it through
+// `car_to_zone`; iterations count the offspring and the moves of their local search
+impl LocalSearch {
+    // "uniform" takes the zone of every car from either parent, "block" takes a random half of the
+    // zones with all the cars the father parks in them and every other car from the mother
+    fn crossover(&self, mother: &Solution, father: &Solution, r: &mut ChaCha12Rng) -> Vec<i32> {
+        match self.config.crossover.as_str() {
+            "uniform" => mother
+                .veh_to_zon
+                .iter()
+                .zip(&father.veh_to_zon)
+                .map(|(zone_m, zone_f)| if r.gen_bool(0.5) { *zone_m } else { *zone_f })
+                .collect(),
+            "block" => {
+                let taken: Vec<bool> = self.zones.iter().map(|_| r.gen_bool(0.5)).collect();
+                mother
+                    .veh_to_zon
+                    .iter()
+                    .zip(&father.veh_to_zon)
+                    .map(|(zone_m, zone_f)| if taken[*zone_f as usize] { *zone_f } else { *zone_m })
+                    .collect()
+            }
+            crossover => panic!("unknown crossover {}", crossover),
+        }
+    }
+
+    // move every car of the live state to its zone in `layout`, reassigning reservations as it goes;
+    // a car that may not go there stays, one sent the other way by a swap may not end up there either
+    fn follow_layout(&mut self, layout: &[i32]) {
+        for (veh, zone) in layout.iter().enumerate() {
+            self.move_car(veh, *zone);
+        }
+    }
+
+    // every car moves to a random neighbouring zone with probability `rate`
+    fn mutate(&mut self, rate: f64, r: &mut ChaCha12Rng) {
+        for veh in 0..self.vehicle.len() {
+            let neighbours = &self.zones[self.veh_to_zon[veh] as usize].neighbours;
+            if neighbours.is_empty() || !self.movable(veh) || !r.gen_bool(rate) {
+                continue;
+            }
+            let zone = neighbours[r.gen_range(0..neighbours.len())];
+            self.move_car(veh, zone);
+        }
+    }
+
+    // descent with the zone moves of `run` from the committed state
+    fn improve(&mut self, start_time: &Instant, time: i32, i: &mut u64, r: &mut ChaCha12Rng) {
+        for _ in 0..self.config.offspring_moves {
+            if !self.running(start_time, time, *i) {
+                return;
+            }
+            *i += 1;
+            let vehicle_id = r.gen_range(0..self.vehicle.len());
+            self.try_neighbour_zones(vehicle_id, 0);
+        }
+    }
+
+    // improve the live state when it is feasible, then offer it to the population and the elite pool;
+    // true if it is a new best
+    fn settle(&mut self, population: &mut Vec<Solution>, start_time: &Instant, time: i32, i: &mut u64, r: &mut ChaCha12Rng) -> bool {
+        if !self.check_all() {
+            self.restore();
+            return false;
+        }
+        self.commit();
+        self.improve(start_time, time, i, r);
+        self.offer_elite();
+
+        let size = self.config.population_size;
+        if let Some(replaced) = admission(population, size, self.config.elite_distance, self.local_cost, &self.local_veh_to_zon) {
+            admit(population, size, replaced, self.committed_solution());
+        }
+        if self.local_cost < self.best_cost {
+            self.promote_local_to_best();
+            return true;
+        }
+        false
+    }
+
+    // binary tournament, the population is sorted cheapest first
+    fn select<'a>(population: &'a [Solution], r: &mut ChaCha12Rng) -> &'a Solution {
+        let a = r.gen_range(0..population.len());
+        let b = r.gen_range(0..population.len());
+        &population[a.min(b)]
+    }
+
+    pub fn run_memetic(&mut self, time: i32, seed: u64) {
+        let mut r = ChaCha12Rng::seed_from_u64(seed);
+        let start_time = Instant::now();
+        let mut population: Vec<Solution> = vec![];
+        let mut i = 0;
+
+        self.construct(seed);
+        self.commit();
+        let constructed = self.committed_solution();
+        for k in 0..self.config.population_size {
+            // the constructed solution always joins, so there is a best to return
+            if k > 0 && !self.running(&start_time, time, i) {
+                break;
+            }
+            self.load_state(&constructed);
+            if k > 0 {
+                self.mutate(INITIAL_MUTATION_RATE, &mut r);
+            }
+            self.paranoid_check(|| format!("initial individual {}", k));
+            if self.settle(&mut population, &start_time, time, &mut i, &mut r) {
+                self.time_to_best = start_time.elapsed().as_secs_f64();
+                self.improved(self.time_to_best, i);
+            }
+        }
+
+        while self.running(&start_time, time, i) && !population.is_empty() {
+            i += 1;
+            let mother = Self::select(&population, &mut r).clone();
+            let father = Self::select(&population, &mut r);
+            let layout = self.crossover(&mother, father, &mut r);
+
+            self.load_state(&mother);
+            self.follow_layout(&layout);
+            self.mutate(self.config.mutation_rate, &mut r);
+            self.paranoid_check(|| format!("offspring at iteration {}", i));
+            if self.settle(&mut population, &start_time, time, &mut i, &mut r) {
+                self.time_to_best = start_time.elapsed().as_secs_f64();
+                self.improved(self.time_to_best, i);
+            }
+        }
+
+        let relinked = self.relink_elite(start_time + Duration::from_secs_f64(time as f64));
+        let polished = self.polish_best();
+        if relinked || polished {
+            self.time_to_best = start_time.elapsed().as_secs_f64();
+            self.improved(self.time_to_best, i);
+        }
+        self.iterations = i;
+
+        self.log(format!("\ncost_end: {} after {i} iterations", self.best_cost));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ls::LocalSearch;
+    use crate::parser;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    // one parking space per zone, so every move into an occupied zone has to swap
+    #[test]
+    fn offspring_keep_hard_capacities() {
+        let (res, mut zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        for zone in zone.iter_mut() {
+            zone.capacity = Some(1);
+        }
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.paranoid = true;
+        ls.construct(0);
+        ls.commit();
+        let constructed = ls.committed_solution();
+        assert!(ls.capacity_holds());
+
+        for seed in 0..10 {
+            let mut r = ChaCha12Rng::seed_from_u64(seed);
+            ls.load_state(&constructed);
+            ls.mutate(1.0, &mut r);
+            assert!(ls.capacity_holds());
+            let mutated = ls.veh_to_zon.clone();
+
+            ls.load_state(&constructed);
+            ls.follow_layout(&mutated);
+            assert!(ls.capacity_holds());
+            assert!(ls.check_all());
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::data_structs::Solution;
+use crate::ls::LocalSearch;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    Cost,      // p1/p2 cost (plus the soft penalties), minimised
+    Served,    // summed priority of the assigned reservations, maximised
+    Neighbour, // reservations served from a neighbouring zone, minimised
+    Balance,   // busiest minus least busy car in minutes, minimised
+}
+
+impl Objective {
+    pub fn parse(name: &str) -> Objective {
+        match name {
+            "cost" => Objective::Cost,
+            "served" => Objective::Served,
+            "neighbour" => Objective::Neighbour,
+            "balance" => Objective::Balance,
+            _ => panic!("unknown objective {}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Objectives {
+    pub cost: i64,
+    pub served: i64,
+    pub neighbour: i64,
+    pub imbalance: i64,
+}
+
+impl Objectives {
+    // every objective as a value to minimise
+    pub fn value(&self, objective: Objective) -> i64 {
+        match objective {
+            Objective::Cost => self.cost,
+            Objective::Served => -self.served,
+            Objective::Neighbour => self.neighbour,
+            Objective::Balance => self.imbalance,
+        }
+    }
+
+    pub fn dominates(&self, other: &Objectives) -> bool {
+        let all = [Objective::Cost, Objective::Served, Objective::Neighbour, Objective::Balance];
+        all.iter().all(|o| self.value(*o) <= other.value(*o)) && self != other
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectiveMode {
+    Lexicographic(Vec<Objective>),
+    Weighted(Vec<(Objective, i64)>),
+}
+
+impl ObjectiveMode {
+    // "lex:served,cost" or "cost=1,served=100"
+    pub fn parse(spec: &str) -> ObjectiveMode {
+        match spec.strip_prefix("lex:") {
+            Some(order) => ObjectiveMode::Lexicographic(order.split(',').map(Objective::parse).collect()),
+            None => ObjectiveMode::Weighted(
+                spec.split(',')
+                    .map(|term| {
+                        let (name, weight) = term.split_once('=').expect("expected objective=weight");
+                        (Objective::parse(name), weight.parse().expect("No number given as weight"))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    // smaller is better, compared element by element
+    pub fn key(&self, objectives: &Objectives) -> Vec<i64> {
+        match self {
+            ObjectiveMode::Lexicographic(order) => order.iter().map(|o| objectives.value(*o)).collect(),
+            ObjectiveMode::Weighted(weights) => vec![weights.iter().map(|(o, w)| w * objectives.value(*o)).sum()],
+        }
+    }
+}
+
+impl LocalSearch {
+    pub fn objectives(&self) -> Objectives {
+        let mut served = 0;
+        let mut neighbour = 0;
+        let mut busiest = i64::MIN;
+        let mut idlest = i64::MAX;
+        for (veh_id, reservations) in self.veh_to_res.iter().enumerate() {
+            let mut busy = 0;
+            for res in reservations {
+                let res = &self.reservations[*res as usize];
+                served += res.priority as i64;
+                busy += res.restime as i64;
+                if res.zone != self.veh_to_zon[veh_id] {
+                    neighbour += 1;
+                }
+            }
+            busiest = busiest.max(busy);
+            idlest = idlest.min(busy);
+        }
+        Objectives {
+            cost: self.calculate_full_cost() as i64,
+            served,
+            neighbour,
+            imbalance: (busiest - idlest).max(0),
+        }
+    }
+
+    fn save_best(&mut self) {
+        self.best_cost = self.calculate_full_cost();
+        self.best_unassigned = self.unassigned.clone();
+        self.best_veh_to_res = self.veh_to_res.clone();
+        self.best_veh_to_zon = self.veh_to_zon.clone();
+    }
+
+    // the zone moves of `run`; a move is kept when `accept` says so, or to escape once the search stagnates
+    fn multi_search<F>(&mut self, time: i32, seed: u64, mut accept: F)
+    where
+        F: FnMut(&mut LocalSearch, &Objectives, &Objectives) -> (bool, bool),
+    {
+        self.construct(seed);
+        self.commit();
+
+        let mut current = self.objectives();
+        accept(self, &current, &current);
+
+        let mut r = StdRng::seed_from_u64(seed);
+        let start_time = Instant::now();
+        let mut age = 1;
+        let mut i = 0;
+
+        // a stop request ends the search like the time limit, the caller then writes the best so far
+        while start_time.elapsed().as_secs() < time as u64
+            && !self.stop.load(Ordering::Relaxed)
+            && self.config.within_budget(i)
+        {
+            i += 1;
+            let vehicle_id: usize = (r.gen::<u16>() % self.vehicle.len() as u16) as usize;
+            let neighbours = self.zones[self.veh_to_zon[vehicle_id] as usize]
+                .neighbours
+                .clone();
+            let mut improved = false;
+            for zone_id in &neighbours {
+                if !self.move_car(vehicle_id, *zone_id) {
+                    continue;
+                }
+                if !self.check_all() {
+                    self.restore();
+                    continue;
+                }
+                let new = self.objectives();
+                let (keep, better) = accept(self, &new, &current);
+                if keep || age > self.config.stagnation_limit {
+                    self.commit();
+                    current = new;
+                    improved |= better;
+                    if better {
+                        self.time_to_best = start_time.elapsed().as_secs_f64();
+                        self.improved(self.time_to_best, i);
+                    }
+                } else {
+                    self.restore();
+                }
+            }
+            age = if improved { 1 } else { age + 1 };
+        }
+        self.iterations = i;
+    }
+
+    pub fn run_multi(&mut self, time: i32, seed: u64, mode: &ObjectiveMode) {
+        let mut best: Option<Vec<i64>> = None;
+        self.multi_search(time, seed, |ls, new, current| {
+            let key = mode.key(new);
+            let better = best.as_ref().is_none_or(|best| key < *best);
+            if better {
+                best = Some(key.clone());
+                ls.save_best();
+            }
+            (key <= mode.key(current), better)
+        });
+
+        self.log(format!("\ncost_end: {} after {} iterations", self.best_cost, self.iterations));
+    }
+
+    // non-dominated solutions found during the search, ordered by cost; the cheapest becomes the best
+    pub fn run_pareto(&mut self, time: i32, seed: u64) -> Vec<(Objectives, Solution)> {
+        let mut archive: Vec<(Objectives, Solution)> = vec![];
+        self.multi_search(time, seed, |ls, new, current| {
+            let added = !archive.iter().any(|(o, _)| o.dominates(new) || o == new);
+            if added {
+                // whatever new dominates costs at least as much, so the cheapest member is either new
+                // or unchanged; the best follows it before the search reports the improvement
+                if archive.iter().all(|(o, _)| new.cost < o.cost) {
+                    ls.save_best();
+                }
+                archive.retain(|(o, _)| !new.dominates(o));
+                archive.push((*new, ls.current_solution()));
+            }
+            (!current.dominates(new), added)
+        });
+
+        archive.sort_by_key(|(o, _)| o.cost);
+        if let Some((_, solution)) = archive.first() {
+            self.best_cost = solution.cost + self.penalty(&solution.veh_to_res, &solution.veh_to_zon, &solution.unassigned);
+            self.best_veh_to_res = solution.veh_to_res.clone();
+            self.best_veh_to_zon = solution.veh_to_zon.clone();
+            self.best_unassigned = solution.unassigned.clone();
+        }
+
+        self.log(format!("\npareto front: {} solutions after {} iterations", archive.len(), self.iterations));
+        archive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser;
+    use crate::reopt::Baseline;
+
+    #[test]
+    fn frozen_cars_stay_put() {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut constructed = LocalSearch::new(res.clone(), zone.clone(), veh.clone());
+        constructed.construct(1);
+        let baseline = constructed.current_solution();
+        let frozen: Vec<i32> = baseline.veh_to_res.iter().filter_map(|reservations| reservations.first().copied()).take(4).collect();
+
+        for mode in [Some(ObjectiveMode::parse("lex:served,cost")), None] {
+            let mut ls = LocalSearch::new(res.clone(), zone.clone(), veh.clone());
+            ls.set_config(Config {
+                max_iterations: Some(500),
+                ..Config::default()
+            });
+            ls.set_baseline(Baseline {
+                solution: baseline.clone(),
+                zone_penalty: 0,
+                move_penalty: 0,
+                frozen: frozen.clone(),
+            });
+            ls.paranoid = true;
+            match mode {
+                Some(mode) => ls.run_multi(3600, 2, &mode),
+                None => {
+                    ls.run_pareto(3600, 2);
+                }
+            }
+
+            for res in &frozen {
+                let car = baseline.veh_to_res.iter().position(|assigned| assigned.contains(res)).unwrap();
+                assert!(ls.best_veh_to_res[car].contains(res));
+                assert_eq!(ls.best_veh_to_zon[car], baseline.veh_to_zon[car]);
+            }
+        }
+    }
+}
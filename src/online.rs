@@ -0,0 +1,202 @@
+use crate::data_structs::Reservation;
+use crate::ls::LocalSearch;
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Insertion {
+    Assigned { vehicle: i32, cost: i32 },
+    Unassigned { cost: i32 },
+    Cancelled,
+}
+
+// the live plan is the best solution, every call starts from it and publishes the result as the new best
+impl LocalSearch {
+    fn load_best(&mut self) {
+        self.load_state(&self.best_state());
+    }
+
+    fn publish(&mut self) {
+        self.commit();
+        self.promote_local_to_best();
+    }
+
+    // try the free cars in the zone of the reservation first, then the ones in a neighbouring zone
+    pub(crate) fn place(&mut self, res_id: usize) -> bool {
+        for veh_id in 0..self.vehicle.len() {
+            if self.vehicle_possible_own(veh_id, res_id) && self.set_vehicle_if_not_interfere(res_id, veh_id) {
+                return true;
+            }
+        }
+        for veh_id in 0..self.vehicle.len() {
+            if self.vehicle_possible_neighbour(veh_id, res_id) && self.set_vehicle_if_not_interfere(res_id, veh_id) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn assignment_of(&self, res_id: i32) -> Insertion {
+        if self.cancelled.contains(&res_id) {
+            return Insertion::Cancelled;
+        }
+        for (veh_id, reservations) in self.best_veh_to_res.iter().enumerate() {
+            if reservations.contains(&res_id) {
+                let res = &self.reservations[res_id as usize];
+                let mut cost = res.upgrade_penalty(&self.vehicle[veh_id]);
+                if res.zone != self.best_veh_to_zon[veh_id] {
+                    cost += res.p2;
+                }
+                return Insertion::Assigned {
+                    vehicle: veh_id as i32,
+                    cost,
+                };
+            }
+        }
+        Insertion::Unassigned {
+            cost: self.reservations[res_id as usize].p1,
+        }
+    }
+
+    // the id of the reservation is replaced by its index, which is returned alongside the assignment
+    pub fn insert_reservation(&mut self, mut res: Reservation) -> (i32, Insertion) {
+        self.load_best();
+
+        let res_id = self.reservations.len() as i32;
+        res.id = res_id;
+        self.reservations.push(res);
+        self.unassigned.push(res_id);
+
+        self.place(res_id as usize);
+        self.publish();
+        self.paranoid_check(|| format!("insert_reservation(req{})", res_id));
+
+        (res_id, self.assignment_of(res_id))
+    }
+
+    // returns the change in cost, the freed car is offered to the unassigned reservations
+    pub fn cancel_reservation(&mut self, res_id: i32) -> i32 {
+        self.load_best();
+        let old_cost = self.local_cost;
+
+        if self.cancelled.contains(&res_id) {
+            return 0;
+        }
+        // a cancelled reservation stays unassigned with nothing to pay and no car to serve it, so the
+        // written solution still lists every reservation once
+        let Some(res) = self.reservations.get_mut(res_id as usize) else {
+            return 0;
+        };
+        res.possible_vehicles.clear();
+        res.categories.clear();
+        res.p1 = 0;
+        self.cancelled.push(res_id);
+
+        if let Some(veh_id) = self.veh_to_res.iter().position(|res| res.contains(&res_id)) {
+            self.veh_to_res[veh_id].retain(|x| *x != res_id);
+            self.unassigned.push(res_id);
+
+            for res in self.unassigned.clone() {
+                if self.vehicle_possible_own(veh_id, res as usize) {
+                    self.set_vehicle_if_not_interfere(res as usize, veh_id);
+                }
+            }
+            for res in self.unassigned.clone() {
+                if self.vehicle_possible_neighbour(veh_id, res as usize) {
+                    self.set_vehicle_if_not_interfere(res as usize, veh_id);
+                }
+            }
+        }
+
+        self.publish();
+        self.paranoid_check(|| format!("cancel_reservation(req{})", res_id));
+        self.local_cost - old_cost
+    }
+
+    // bounded improvement of the live plan with the zone moves of `run`, returns the new cost
+    pub fn repair(&mut self, iterations: u32, seed: u64) -> i32 {
+        self.load_best();
+
+        let mut r = StdRng::seed_from_u64(seed);
+        for _ in 0..iterations {
+            let vehicle_id: usize = (r.gen::<u16>() % self.vehicle.len() as u16) as usize;
+            self.try_neighbour_zones(vehicle_id, 0);
+        }
+
+        self.publish();
+        self.best_cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser;
+    use crate::validator::validate;
+
+    fn solved() -> LocalSearch {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.set_config(Config {
+            max_iterations: Some(2000),
+            ..Config::default()
+        });
+        ls.paranoid = true;
+        ls.solve(3600, 1);
+        ls
+    }
+
+    fn assert_valid(ls: &LocalSearch) {
+        let solution = ls.best_solution();
+        assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution), Ok(solution.cost));
+    }
+
+    #[test]
+    fn inserted_reservation_is_written_once() {
+        let mut ls = solved();
+        let before = ls.best_cost;
+        let (res_id, insertion) = ls.insert_reservation(ls.reservations[0].clone());
+
+        assert_eq!(res_id as usize, ls.reservations.len() - 1);
+        assert_eq!(insertion, ls.assignment_of(res_id));
+        match insertion {
+            Insertion::Assigned { cost, .. } | Insertion::Unassigned { cost } => assert_eq!(ls.best_cost, before + cost),
+            Insertion::Cancelled => panic!("req{} was never cancelled", res_id),
+        }
+        assert_valid(&ls);
+    }
+
+    #[test]
+    fn cancelled_reservation_stays_unassigned_at_no_cost() {
+        let mut ls = solved();
+        let assigned = ls.best_veh_to_res.iter().flatten().copied().next().expect("a served reservation");
+        let unassigned = ls.best_unassigned[0];
+
+        for res_id in [assigned, unassigned] {
+            let before = ls.best_cost;
+            let delta = ls.cancel_reservation(res_id);
+            assert_eq!(ls.best_cost, before + delta);
+            assert_eq!(ls.assignment_of(res_id), Insertion::Cancelled);
+            assert!(ls.best_unassigned.contains(&res_id));
+            assert_valid(&ls);
+        }
+        assert_eq!(ls.cancel_reservation(assigned), 0);
+    }
+
+    #[test]
+    fn repair_keeps_the_plan_valid() {
+        let mut ls = solved();
+        ls.insert_reservation(ls.reservations[1].clone());
+        let served = ls.best_veh_to_res.iter().flatten().copied().next().expect("a served reservation");
+        ls.cancel_reservation(served);
+        let before = ls.best_cost;
+
+        let cost = ls.repair(500, 3);
+        assert_eq!(cost, ls.best_cost);
+        assert!(cost <= before);
+        assert!(ls.check_all());
+        assert_valid(&ls);
+    }
+}
@@ -1,97 +1,519 @@
-use std::fs;
-
-use crate::data_structs::{Reservation, Zone, Vehicle};
-
-fn strip_trailing_newline(input: &str) -> &str
-{
-    input
-        .strip_suffix("\r")
-        .or(input.strip_suffix("\n"))
-        .unwrap_or(input)
-}
-
-pub fn read_file(filepath: &str) -> (Vec<Reservation>, Vec<Zone>, Vec<Vehicle>)
-{
-
-    let lines = fs::read_to_string(filepath)
-                    .expect("could not read file");
-
-    let mut amount_requests = 0;
-    let mut amount_zones = 0;
-    let mut amount_vehicles = 0;
-    let mut vec_reservations: Vec<Reservation> = Vec::new();
-    let mut vec_zones: Vec<Zone> = Vec::new();
-    let mut vec_vehicles: Vec<Vehicle> = Vec::new();
-    
-    for (i, line) in lines.split("\n").enumerate()
-    {
-        let contents = line.split(";").collect::<Vec<&str>>();
-
-        if i == 0 // get amount of reservations
-        {
-            let string = contents[0].split(": ").collect::<Vec<&str>>()[1];
-            amount_requests = strip_trailing_newline(string).parse().unwrap();
-            // println!("amount_requests: {amount_requests}");
-            continue;
-        }
-
-        if i <= amount_requests // read in all requests
-        {
-            let id: i32 = contents[0][3..].parse().unwrap();
-            let zone: i32 = contents[1][1..].parse().unwrap();
-            let day: i32 = contents[2].parse().unwrap();
-            let start: i32 = contents[3].parse::<i32>().unwrap() + 1440*day;
-            let restime: i32 = contents[4].parse().unwrap();
-            let pos_veh: Vec<&str> = contents[5].split(',').collect();
-            let mut possible_vehicles: Vec<i32> = Vec::new();
-            for pos in pos_veh
-            {
-                let veh = pos[3..].parse::<i32>().unwrap();
-                possible_vehicles.push(veh);
-            }
-            let p1: i32 = contents[6].parse().unwrap();
-            let string = contents[7];
-            let p2: i32 = strip_trailing_newline(string).parse().unwrap();
-            let res: Reservation = Reservation { id, zone, day, start, restime, possible_vehicles, p1, p2, vehicle: None};
-
-            vec_reservations.push(res);
-            continue;
-        }
-
-        if i == amount_requests + 1 // get amount of zones
-        {
-            let string = contents[0].split(": ").collect::<Vec<&str>>()[1];
-            amount_zones = strip_trailing_newline(string).parse().unwrap();
-            // println!("amount_zones: {amount_zones}");
-            continue;
-        }
-
-        if i <= amount_requests + amount_zones + 1 // reading zones
-        {
-            let id: i32 = contents[0][1..].parse().unwrap();
-            let strings: Vec<&str> = contents[1].split(',').collect();
-            let mut zones: Vec<i32> = Vec::new();
-            for s in strings
-            {
-                zones.push(strip_trailing_newline(&s[1..]).parse::<i32>().unwrap());
-            }
-            vec_zones.push(Zone{ id, neighbours: zones });
-            continue;
-        }
-
-        if i == amount_requests + amount_zones + 2 // reading amount of vehicles
-        {
-            let string = contents[0].split(": ").collect::<Vec<&str>>()[1];
-            amount_vehicles = strip_trailing_newline(string).parse().unwrap();
-            // println!("amount_vehicles: {amount_vehicles}");
-            continue;
-        }
-
-        if i <= amount_requests + amount_zones + amount_vehicles + 2
-        {
-            vec_vehicles.push(Vehicle { id: strip_trailing_newline(&contents[0][3..]).parse().unwrap(), zone: None});
-            continue;
-        }
-    }
-    return (vec_reservations, vec_zones, vec_vehicles);
-}
\ No newline at end of file
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use crate::data_structs::{Constraints, Instance, Reservation, Solution, Zone, Vehicle};
+
+fn strip_trailing_newline(input: &str) -> &str
+{
+    input
+        .strip_suffix("\r")
+        .or(input.strip_suffix("\n"))
+        .unwrap_or(input)
+}
+
+pub fn read_file(filepath: &str) -> (Vec<Reservation>, Vec<Zone>, Vec<Vehicle>)
+{
+
+    let lines = fs::read_to_string(filepath)
+                    .expect("could not read file");
+
+    let mut amount_requests = 0;
+    let mut amount_zones = 0;
+    let mut amount_vehicles = 0;
+    let mut vec_reservations: Vec<Reservation> = Vec::new();
+    let mut vec_zones: Vec<Zone> = Vec::new();
+    let mut vec_vehicles: Vec<Vehicle> = Vec::new();
+    let mut availability_section = false;
+    
+    for (i, line) in lines.split("\n").enumerate()
+    {
+        let contents = line.split(";").collect::<Vec<&str>>();
+
+        if i == 0 // get amount of reservations
+        {
+            let string = contents[0].split(": ").collect::<Vec<&str>>()[1];
+            amount_requests = strip_trailing_newline(string).parse().unwrap();
+            // println!("amount_requests: {amount_requests}");
+            continue;
+        }
+
+        if i <= amount_requests // read in all requests
+        {
+            let id: i32 = contents[0][3..].parse().unwrap();
+            let zone: i32 = contents[1][1..].parse().unwrap();
+            let day: i32 = contents[2].parse().unwrap();
+            let start: i32 = contents[3].parse::<i32>().unwrap() + 1440*day;
+            let restime: i32 = contents[4].parse().unwrap();
+            let mut possible_vehicles: Vec<i32> = Vec::new();
+            let mut categories: Vec<(String, i32)> = Vec::new();
+            if let Some(cats) = contents[5].strip_prefix("cat:") // category form: cat:compact,van+50
+            {
+                for cat in cats.split(',')
+                {
+                    match cat.split_once('+')
+                    {
+                        Some((name, penalty)) => categories.push((name.to_string(), penalty.parse().unwrap())),
+                        None => categories.push((cat.to_string(), 0))
+                    }
+                }
+            }
+            else
+            {
+                let pos_veh: Vec<&str> = contents[5].split(',').collect();
+                for pos in pos_veh
+                {
+                    let veh = pos[3..].parse::<i32>().unwrap();
+                    possible_vehicles.push(veh);
+                }
+            }
+            let p1: i32 = contents[6].parse().unwrap();
+            let string = contents[7];
+            let p2: i32 = strip_trailing_newline(string).parse().unwrap();
+            // optional ninth field: priority of the reservation
+            let priority: i32 = contents.get(8).map_or(1, |p| strip_trailing_newline(p).parse().unwrap());
+            let res: Reservation = Reservation { id, zone, day, start, restime, possible_vehicles, categories, p1, p2, priority, vehicle: None};
+
+            vec_reservations.push(res);
+            continue;
+        }
+
+        if i == amount_requests + 1 // get amount of zones
+        {
+            let string = contents[0].split(": ").collect::<Vec<&str>>()[1];
+            amount_zones = strip_trailing_newline(string).parse().unwrap();
+            // println!("amount_zones: {amount_zones}");
+            continue;
+        }
+
+        if i <= amount_requests + amount_zones + 1 // reading zones
+        {
+            let id: i32 = contents[0][1..].parse().unwrap();
+            let strings: Vec<&str> = contents[1].split(',').collect();
+            let mut zones: Vec<i32> = Vec::new();
+            for s in strings
+            {
+                zones.push(strip_trailing_newline(&s[1..]).parse::<i32>().unwrap());
+            }
+            // optional third field: capacity of the zone
+            let capacity = contents.get(2)
+                                   .map(|c| strip_trailing_newline(c))
+                                   .filter(|c| !c.is_empty())
+                                   .map(|c| c.parse::<i32>().unwrap());
+            vec_zones.push(Zone{ id, neighbours: zones, capacity });
+            continue;
+        }
+
+        if i == amount_requests + amount_zones + 2 // reading amount of vehicles
+        {
+            let string = contents[0].split(": ").collect::<Vec<&str>>()[1];
+            amount_vehicles = strip_trailing_newline(string).parse().unwrap();
+            // println!("amount_vehicles: {amount_vehicles}");
+            continue;
+        }
+
+        if i <= amount_requests + amount_zones + amount_vehicles + 2
+        {
+            // optional second field: category of the vehicle
+            let category = contents.get(1)
+                                   .map(|c| strip_trailing_newline(c).to_string())
+                                   .filter(|c| !c.is_empty());
+            vec_vehicles.push(Vehicle { id: strip_trailing_newline(&contents[0][3..]).parse().unwrap(), zone: None, availability: Vec::new(), category});
+            continue;
+        }
+
+        // optional, one line per car: car3;z2;0-600,900-2880 (zone and windows may be left empty)
+        if line.starts_with("+Availability")
+        {
+            availability_section = true;
+            continue;
+        }
+
+        if availability_section && !strip_trailing_newline(line).is_empty()
+        {
+            let veh: usize = contents[0][3..].parse().unwrap();
+            let zone = strip_trailing_newline(contents[1]);
+            if !zone.is_empty()
+            {
+                vec_vehicles[veh].zone = Some(zone[1..].parse().unwrap());
+            }
+            let windows = strip_trailing_newline(contents.get(2).unwrap_or(&""));
+            for window in windows.split(',').filter(|w| !w.is_empty())
+            {
+                let (start, end) = window.split_once('-').unwrap();
+                vec_vehicles[veh].availability.push((start.parse().unwrap(), end.parse().unwrap()));
+            }
+            continue;
+        }
+    }
+    (vec_reservations, vec_zones, vec_vehicles)
+}
+
+pub fn read_solution(filepath: &str) -> Solution
+{
+    let lines = fs::read_to_string(filepath)
+                    .expect("could not read file");
+
+    let mut cost = 0;
+    let mut veh_to_zon: Vec<i32> = Vec::new();
+    let mut veh_to_res: Vec<Vec<i32>> = Vec::new();
+    let mut unassigned: Vec<i32> = Vec::new();
+    let mut section = "";
+
+    for (i, line) in lines.split("\n").enumerate()
+    {
+        let line = strip_trailing_newline(line);
+        if line.is_empty()
+        {
+            continue;
+        }
+
+        if i == 0 // cost of the solution
+        {
+            cost = line.parse().unwrap();
+            continue;
+        }
+
+        if line.starts_with('+')
+        {
+            section = line;
+            continue;
+        }
+
+        let contents = line.split(";").collect::<Vec<&str>>();
+        match section
+        {
+            "+Vehicle assignments" =>
+            {
+                let veh: usize = contents[0][3..].parse().unwrap();
+                if veh >= veh_to_zon.len()
+                {
+                    veh_to_zon.resize(veh + 1, 0);
+                    veh_to_res.resize(veh + 1, vec![]);
+                }
+                veh_to_zon[veh] = contents[1][1..].parse().unwrap();
+            }
+            "+Assigned requests" =>
+            {
+                let res: i32 = contents[0][3..].parse().unwrap();
+                let veh: usize = contents[1][3..].parse().unwrap();
+                veh_to_res[veh].push(res);
+            }
+            "+Unassigned requests" =>
+            {
+                unassigned.push(contents[0][3..].parse().unwrap());
+            }
+            _ => {}
+        }
+    }
+    Solution { cost, veh_to_zon, veh_to_res, unassigned }
+}
+
+
+pub fn write_file(filepath: &str, reservations: &[Reservation], zones: &[Zone], vehicles: &[Vehicle]) -> std::io::Result<()>
+{
+    let mut file = File::create(filepath)?;
+
+    file.write_all(format!("+Requests: {}\n", reservations.len()).as_bytes())?;
+    for res in reservations
+    {
+        let possible_vehicles = if res.categories.is_empty()
+        {
+            res.possible_vehicles.iter()
+               .map(|veh| format!("car{veh}"))
+               .collect::<Vec<String>>()
+               .join(",")
+        }
+        else
+        {
+            let categories = res.categories.iter()
+                                .map(|(cat, penalty)| if *penalty == 0 { cat.clone() } else { format!("{cat}+{penalty}") })
+                                .collect::<Vec<String>>()
+                                .join(",");
+            format!("cat:{categories}")
+        };
+        let priority = if res.priority != 1 { format!(";{}", res.priority) } else { String::new() };
+        file.write_all(format!("req{};z{};{};{};{};{};{};{}{}\n",
+                               res.id, res.zone, res.day, res.start - 1440*res.day, res.restime,
+                               possible_vehicles, res.p1, res.p2, priority).as_bytes())?;
+    }
+
+    file.write_all(format!("+Zones: {}\n", zones.len()).as_bytes())?;
+    for zone in zones
+    {
+        let neighbours = zone.neighbours.iter()
+                             .map(|zon| format!("z{zon}"))
+                             .collect::<Vec<String>>()
+                             .join(",");
+        match zone.capacity
+        {
+            Some(capacity) => file.write_all(format!("z{};{};{}\n", zone.id, neighbours, capacity).as_bytes())?,
+            None => file.write_all(format!("z{};{}\n", zone.id, neighbours).as_bytes())?
+        }
+    }
+
+    file.write_all(format!("+Vehicles: {}\n", vehicles.len()).as_bytes())?;
+    for veh in vehicles
+    {
+        match &veh.category
+        {
+            Some(category) => file.write_all(format!("car{};{}\n", veh.id, category).as_bytes())?,
+            None => file.write_all(format!("car{}\n", veh.id).as_bytes())?
+        }
+    }
+
+    let days = reservations.iter().map(|res| res.day + 1).max().unwrap_or(0);
+    file.write_all(format!("+Days: {}\n", days).as_bytes())?;
+
+    let restricted: Vec<&Vehicle> = vehicles.iter()
+                                        .filter(|veh| veh.zone.is_some() || !veh.availability.is_empty())
+                                        .collect();
+    if !restricted.is_empty()
+    {
+        file.write_all(format!("+Availability: {}\n", restricted.len()).as_bytes())?;
+        for veh in restricted
+        {
+            let zone = veh.zone.map(|zon| format!("z{zon}")).unwrap_or_default();
+            let windows = veh.availability.iter()
+                             .map(|(start, end)| format!("{start}-{end}"))
+                             .collect::<Vec<String>>()
+                             .join(",");
+            file.write_all(format!("car{};{};{}\n", veh.id, zone, windows).as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+pub fn read_json(filepath: &str) -> (Vec<Reservation>, Vec<Zone>, Vec<Vehicle>)
+{
+    let contents = fs::read_to_string(filepath)
+                    .expect("could not read file");
+    let instance: Instance = serde_json::from_str(&contents)
+                    .expect("could not parse instance");
+    (instance.reservations, instance.zones, instance.vehicles)
+}
+
+pub fn write_json(filepath: &str, reservations: Vec<Reservation>, zones: Vec<Zone>, vehicles: Vec<Vehicle>) -> std::io::Result<()>
+{
+    let instance = Instance { reservations, zones, vehicles };
+    fs::write(filepath, serde_json::to_string_pretty(&instance)?)
+}
+
+pub fn write_solution(filepath: &str, solution: &Solution) -> std::io::Result<()>
+{
+    let mut file = File::create(filepath)?;
+
+    file.write_all(format!("{}\n", solution.cost).as_bytes())?;
+    file.write_all(b"+Vehicle assignments\n")?;
+    for (i, zone) in solution.veh_to_zon.iter().enumerate()
+    {
+        file.write_all(format!("car{};z{}\n", i, zone).as_bytes())?;
+    }
+
+    file.write_all(b"+Assigned requests\n")?;
+    for (i, veh) in solution.veh_to_res.iter().enumerate()
+    {
+        for req in veh
+        {
+            file.write_all(format!("req{};car{}\n", req, i).as_bytes())?;
+        }
+    }
+
+    file.write_all(b"+Unassigned requests\n")?;
+    for res in &solution.unassigned
+    {
+        file.write_all(format!("req{}\n", res).as_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn read_solution_json(filepath: &str) -> Solution
+{
+    let contents = fs::read_to_string(filepath)
+                    .expect("could not read file");
+    serde_json::from_str(&contents)
+        .expect("could not parse solution")
+}
+
+pub fn write_solution_json(filepath: &str, solution: &Solution) -> std::io::Result<()>
+{
+    fs::write(filepath, serde_json::to_string_pretty(solution)?)
+}
+
+// format is "json" or "csv", without one the extension of the file decides
+pub fn is_json(filepath: &str, format: Option<&str>) -> bool
+{
+    match format
+    {
+        Some(format) => format == "json",
+        None => Path::new(filepath).extension().is_some_and(|ext| ext == "json")
+    }
+}
+
+pub fn load_instance(filepath: &str, format: Option<&str>) -> (Vec<Reservation>, Vec<Zone>, Vec<Vehicle>)
+{
+    if is_json(filepath, format)
+    {
+        read_json(filepath)
+    }
+    else
+    {
+        read_file(filepath)
+    }
+}
+
+pub fn load_solution(filepath: &str, format: Option<&str>) -> Solution
+{
+    if is_json(filepath, format)
+    {
+        read_solution_json(filepath)
+    }
+    else
+    {
+        read_solution(filepath)
+    }
+}
+
+pub fn save_solution(filepath: &str, solution: &Solution, format: Option<&str>) -> std::io::Result<()>
+{
+    if is_json(filepath, format)
+    {
+        write_solution_json(filepath, solution)
+    }
+    else
+    {
+        write_solution(filepath, solution)
+    }
+}
+
+// sections: +Pinned (req;car), +Fixed zones (car;zone), +Forbidden (req;car), +Out of service (car;day)
+pub fn read_constraints(filepath: &str) -> Constraints
+{
+    let lines = fs::read_to_string(filepath)
+                    .expect("could not read file");
+
+    let mut constraints = Constraints::default();
+    let mut section = "";
+
+    for line in lines.split("\n")
+    {
+        let line = strip_trailing_newline(line);
+        if line.is_empty()
+        {
+            continue;
+        }
+
+        if line.starts_with('+')
+        {
+            section = line.split(":").next().unwrap();
+            continue;
+        }
+
+        let contents = line.split(";").collect::<Vec<&str>>();
+        match section
+        {
+            "+Pinned" =>
+            {
+                constraints.pinned.push((contents[0][3..].parse().unwrap(), contents[1][3..].parse().unwrap()));
+            }
+            "+Fixed zones" =>
+            {
+                constraints.fixed_zones.push((contents[0][3..].parse().unwrap(), contents[1][1..].parse().unwrap()));
+            }
+            "+Forbidden" =>
+            {
+                constraints.forbidden.push((contents[0][3..].parse().unwrap(), contents[1][3..].parse().unwrap()));
+            }
+            "+Out of service" =>
+            {
+                constraints.out_of_service.push((contents[0][3..].parse().unwrap(), contents[1].parse().unwrap()));
+            }
+            _ => {}
+        }
+    }
+    constraints
+}
+
+// the solution replaces the file in one rename, readers never see a half written file
+pub fn save_solution_atomic(filepath: &str, solution: &Solution, format: Option<&str>) -> std::io::Result<()>
+{
+    let tmp = format!("{filepath}.tmp");
+    if is_json(filepath, format)
+    {
+        write_solution_json(&tmp, solution)?;
+    }
+    else
+    {
+        write_solution(&tmp, solution)?;
+    }
+    fs::rename(&tmp, filepath)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const INSTANCES: [&str; 3] = ["input/toy1.csv", "input/100_5_14_25.csv", "input/360_5_71_25.csv"];
+
+    fn tmp_file(name: &str) -> String
+    {
+        std::env::temp_dir()
+            .join(format!("{}_{}", std::process::id(), name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn csv_round_trip()
+    {
+        for instance in INSTANCES
+        {
+            let (res, zones, vehicles) = read_file(instance);
+            let tmp = tmp_file("round_trip.csv");
+            write_file(&tmp, &res, &zones, &vehicles).unwrap();
+            let again = read_file(&tmp);
+            fs::remove_file(&tmp).unwrap();
+
+            assert!(!res.is_empty(), "{instance}");
+            assert_eq!(again, (res, zones, vehicles), "{instance}");
+        }
+    }
+
+    #[test]
+    fn json_round_trip()
+    {
+        for instance in INSTANCES
+        {
+            let (res, zones, vehicles) = read_file(instance);
+            let tmp = tmp_file("round_trip.json");
+            write_json(&tmp, res.clone(), zones.clone(), vehicles.clone()).unwrap();
+            let again = read_json(&tmp);
+            fs::remove_file(&tmp).unwrap();
+
+            assert_eq!(again, (res, zones, vehicles), "{instance}");
+        }
+    }
+
+    #[test]
+    fn solution_round_trip()
+    {
+        let solution = Solution
+        {
+            cost: 140,
+            veh_to_zon: vec![1, 4, 3],
+            veh_to_res: vec![vec![8, 5], vec![], vec![6, 0]],
+            unassigned: vec![3, 1],
+        };
+        for name in ["solution.csv", "solution.json"]
+        {
+            let tmp = tmp_file(name);
+            save_solution(&tmp, &solution, None).unwrap();
+            let again = load_solution(&tmp, None);
+            fs::remove_file(&tmp).unwrap();
+
+            assert_eq!(again, solution, "{name}");
+        }
+    }
+}
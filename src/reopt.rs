@@ -0,0 +1,151 @@
+use crate::data_structs::Solution;
+use crate::ls::LocalSearch;
+
+pub struct Baseline {
+    pub solution: Solution,
+    pub zone_penalty: i32, // per car parked in another zone than in the baseline
+    pub move_penalty: i32, // per reservation taken away from its baseline car
+    pub frozen: Vec<i32>,  // reservations that stay on their baseline car, which then keeps its zone
+}
+
+impl LocalSearch {
+    pub fn set_baseline(&mut self, baseline: Baseline) {
+        self.baseline_vehicle = vec![None; self.reservations.len()];
+        for (veh, reservations) in baseline.solution.veh_to_res.iter().enumerate() {
+            for res in reservations {
+                if let Some(slot) = self.baseline_vehicle.get_mut(*res as usize) {
+                    *slot = Some(veh as i32);
+                }
+            }
+        }
+        self.baseline = Some(baseline);
+    }
+
+    // penalty for deviating from the baseline, 0 without one
+    pub(crate) fn disruption(&self, veh_to_res: &[Vec<i32>], veh_to_zon: &[i32], unassigned: &[i32]) -> i32 {
+        let baseline = match &self.baseline {
+            Some(baseline) => baseline,
+            None => return 0,
+        };
+
+        let mut sum = 0;
+        for (veh, zone) in veh_to_zon.iter().enumerate() {
+            if baseline.solution.veh_to_zon.get(veh).is_some_and(|z| z != zone) {
+                sum += baseline.zone_penalty;
+            }
+        }
+        for (veh, reservations) in veh_to_res.iter().enumerate() {
+            for res in reservations {
+                if self.baseline_vehicle.get(*res as usize).copied().flatten().is_some_and(|v| v != veh as i32) {
+                    sum += baseline.move_penalty;
+                }
+            }
+        }
+        for res in unassigned {
+            if self.baseline_vehicle.get(*res as usize).copied().flatten().is_some() {
+                sum += baseline.move_penalty;
+            }
+        }
+        sum
+    }
+
+    pub(crate) fn is_frozen_vehicle(&self, veh_id: usize) -> bool {
+        match &self.baseline {
+            Some(baseline) => baseline
+                .frozen
+                .iter()
+                .any(|res| self.baseline_vehicle.get(*res as usize).copied().flatten() == Some(veh_id as i32)),
+            None => false,
+        }
+    }
+
+    // start from the baseline, reservations that no longer fit their car are placed again
+    pub(crate) fn load_baseline(&mut self) {
+        let solution = self.baseline.as_ref().expect("no baseline set").solution.clone();
+
+        for veh_id in 0..self.vehicle.len() {
+            self.veh_to_zon[veh_id] = self
+                .constraints
+                .fixed_zone(veh_id as i32)
+                .unwrap_or(solution.veh_to_zon.get(veh_id).copied().unwrap_or(0));
+            self.veh_to_res[veh_id] = vec![];
+        }
+        self.unassigned = (0..self.reservations.len() as i32).collect();
+
+        for (veh_id, reservations) in solution.veh_to_res.iter().enumerate() {
+            if veh_id >= self.vehicle.len() {
+                continue;
+            }
+            for res in reservations {
+                let res = *res as usize;
+                if res < self.reservations.len()
+                    && (self.vehicle_possible_own(veh_id, res) || self.vehicle_possible_neighbour(veh_id, res))
+                {
+                    self.set_vehicle_if_not_interfere(res, veh_id);
+                }
+            }
+        }
+
+        for res in self.unassigned.clone() {
+            self.place(res as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::parser;
+    use crate::validator::validate;
+
+    fn solved(baseline: Baseline, seed: u64) -> LocalSearch {
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.set_config(Config {
+            max_iterations: Some(2000),
+            ..Config::default()
+        });
+        ls.set_baseline(baseline);
+        ls.paranoid = true;
+        ls.solve(3600, seed);
+        ls
+    }
+
+    fn car_of(solution: &Solution, res: i32) -> Option<usize> {
+        solution.veh_to_res.iter().position(|reservations| reservations.contains(&res))
+    }
+
+    #[test]
+    fn frozen_reservations_stay_and_deviations_are_paid() {
+        // the constructed solution leaves the search plenty to change
+        let (res, zone, veh) = parser::read_file("input/100_5_14_25.csv");
+        let mut constructed = LocalSearch::new(res, zone, veh);
+        constructed.construct(1);
+        let old = constructed.current_solution();
+        let frozen: Vec<i32> = old.veh_to_res.iter().filter_map(|reservations| reservations.first().copied()).take(3).collect();
+        let (zone_penalty, move_penalty) = (3, 2);
+        let baseline = Baseline {
+            solution: old.clone(),
+            zone_penalty,
+            move_penalty,
+            frozen: frozen.clone(),
+        };
+        let ls = solved(baseline, 2);
+        let new = ls.best_solution();
+        assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &new), Ok(new.cost));
+
+        for res in &frozen {
+            let veh = car_of(&old, *res).unwrap();
+            assert_eq!(car_of(&new, *res), Some(veh));
+            assert_eq!(new.veh_to_zon[veh], old.veh_to_zon[veh]);
+        }
+
+        let zones_changed = (0..ls.vehicle.len()).filter(|veh| new.veh_to_zon[*veh] != old.veh_to_zon[*veh]).count() as i32;
+        let moved = (0..ls.reservations.len() as i32)
+            .filter(|res| car_of(&old, *res).is_some_and(|veh| car_of(&new, *res) != Some(veh)))
+            .count() as i32;
+        assert!(zones_changed > 0 && moved > 0);
+        assert_eq!(ls.best_cost, new.cost + zone_penalty * zones_changed + move_penalty * moved);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::data_structs::{Reservation, Solution, Vehicle, Zone};
+use crate::explain::{explain, Explanation};
+use color_print::cformat;
+use std::{fs::File, io::Write};
+
+const MINUTES_PER_COLUMN: i32 = 30;
+const COLUMNS_PER_DAY: i32 = 1440 / MINUTES_PER_COLUMN;
+
+fn amount_days(reservations: &[Reservation]) -> i32 {
+    // a reservation can run past midnight of the last day
+    let last = reservations
+        .iter()
+        .map(|res| res.start + res.restime)
+        .max()
+        .unwrap_or(0);
+    (last + 1439) / 1440
+}
+
+fn clock(minutes: i32) -> String {
+    format!("d{} {:02}:{:02}", minutes / 1440, (minutes % 1440) / 60, minutes % 60)
+}
+
+fn is_neighbour_assignment(reservations: &[Reservation], solution: &Solution, res_id: i32, veh: usize) -> bool {
+    reservations[res_id as usize].zone != solution.veh_to_zon[veh]
+}
+
+pub fn write_report(out: &mut impl Write, reservations: &[Reservation], zones: &[Zone], vehicles: &[Vehicle], solution: &Solution) -> std::io::Result<()> {
+    let days = amount_days(reservations);
+
+    writeln!(out, "{}", cformat!("<bold>cost: {}</>", solution.cost))?;
+    writeln!(out, "{}", cformat!("<green>#</> own zone  <yellow>#</> neighbouring zone (p2)  <dim>|</> midnight\n"))?;
+
+    for (veh, assigned) in solution.veh_to_res.iter().enumerate() {
+        let mut row = String::new();
+        for column in 0..days * COLUMNS_PER_DAY {
+            if column > 0 && column % COLUMNS_PER_DAY == 0 {
+                row.push_str(&cformat!("<dim>|</>"));
+            }
+            let from = column * MINUTES_PER_COLUMN;
+            let to = from + MINUTES_PER_COLUMN;
+            let covering = assigned.iter().find(|res_id| {
+                let res = &reservations[**res_id as usize];
+                res.start < to && from < res.start + res.restime
+            });
+            match covering {
+                Some(res_id) if is_neighbour_assignment(reservations, solution, *res_id, veh) => {
+                    row.push_str(&cformat!("<yellow>#</>"))
+                }
+                Some(_) => row.push_str(&cformat!("<green>#</>")),
+                None => row.push('.'),
+            }
+        }
+        writeln!(out, "{}", cformat!("<bold>car{:<3}</> z{:<3} {}", veh, solution.veh_to_zon[veh], row))?;
+
+        let mut sorted = assigned.clone();
+        sorted.sort_by_key(|res_id| reservations[*res_id as usize].start);
+        for res_id in sorted {
+            let res = &reservations[res_id as usize];
+            let line = format!(
+                "req{} z{} {} - {}",
+                res.id,
+                res.zone,
+                clock(res.start),
+                clock(res.start + res.restime)
+            );
+            if is_neighbour_assignment(reservations, solution, res_id, veh) {
+                writeln!(out, "{}", cformat!("           <yellow>{} (p2 {})</>", line, res.p2))?;
+            } else {
+                writeln!(out, "           {}", line)?;
+            }
+        }
+    }
+
+    writeln!(out, "{}", cformat!("\n<bold>unassigned: {}</>", solution.unassigned.len()))?;
+    for res_id in &solution.unassigned {
+        let res = &reservations[*res_id as usize];
+        writeln!(
+            out,
+            "{}",
+            cformat!(
+                "<red>req{}</> z{} {} - {} (p1 {})",
+                res.id,
+                res.zone,
+                clock(res.start),
+                clock(res.start + res.restime),
+                res.p1
+            )
+        )?;
+        write_explanation(out, &explain(reservations, zones, vehicles, solution, *res_id))?;
+    }
+    Ok(())
+}
+
+pub fn write_explanation(out: &mut impl Write, explanation: &Explanation) -> std::io::Result<()> {
+    let cheapest = explanation.cheapest().map(|veh| veh.vehicle);
+    for veh in &explanation.vehicles {
+        if Some(veh.vehicle) == cheapest {
+            writeln!(out, "{}", cformat!("    <bold>{}</>", veh))?;
+        } else {
+            writeln!(out, "    {}", veh)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn write_html(filename: &str, reservations: &[Reservation], zones: &[Zone], vehicles: &[Vehicle], solution: &Solution) -> std::io::Result<()> {
+    let mut file = File::create(filename)?;
+
+    let days = amount_days(reservations);
+    let label_width = 90;
+    let row_height = 24;
+    let width = label_width + days * COLUMNS_PER_DAY * 10;
+    let height = row_height * (solution.veh_to_res.len() as i32 + 1);
+    let scale = 10.0 / MINUTES_PER_COLUMN as f64;
+
+    file.write_all(b"<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Solution report</title></head><body>\n")?;
+    file.write_all(format!("<h1>Cost: {}</h1>\n", solution.cost).as_bytes())?;
+    file.write_all(format!("<svg width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"12\">\n", width, height).as_bytes())?;
+
+    for day in 0..=days {
+        let x = label_width as f64 + (day * 1440) as f64 * scale;
+        file.write_all(format!("<line x1=\"{x}\" y1=\"0\" x2=\"{x}\" y2=\"{height}\" stroke=\"#ccc\"/>\n").as_bytes())?;
+        if day < days {
+            file.write_all(format!("<text x=\"{}\" y=\"{}\">day {}</text>\n", x + 4.0, height - 6, day).as_bytes())?;
+        }
+    }
+
+    for (veh, assigned) in solution.veh_to_res.iter().enumerate() {
+        let y = veh as i32 * row_height;
+        file.write_all(
+            format!("<text x=\"4\" y=\"{}\">car{} z{}</text>\n", y + 16, veh, solution.veh_to_zon[veh]).as_bytes(),
+        )?;
+        for res_id in assigned {
+            let res = &reservations[*res_id as usize];
+            let neighbour = is_neighbour_assignment(reservations, solution, *res_id, veh);
+            let colour = if neighbour { "#f0a030" } else { "#40a040" };
+            let cost = if neighbour { format!(" (p2 {})", res.p2) } else { String::new() };
+            file.write_all(
+                format!(
+                    "<rect x=\"{:.1}\" y=\"{}\" width=\"{:.1}\" height=\"{}\" fill=\"{}\"><title>req{} z{} {} - {}{}</title></rect>\n",
+                    label_width as f64 + res.start as f64 * scale,
+                    y + 4,
+                    res.restime as f64 * scale,
+                    row_height - 8,
+                    colour,
+                    res.id,
+                    res.zone,
+                    clock(res.start),
+                    clock(res.start + res.restime),
+                    cost
+                )
+                .as_bytes(),
+            )?;
+        }
+    }
+    file.write_all(b"</svg>\n")?;
+
+    file.write_all(format!("<h2>Unassigned: {}</h2>\n<table border=\"1\">\n", solution.unassigned.len()).as_bytes())?;
+    file.write_all(b"<tr><th>request</th><th>zone</th><th>time</th><th>p1</th><th>cars</th><th>cheapest repair</th></tr>\n")?;
+    for res_id in &solution.unassigned {
+        let res = &reservations[*res_id as usize];
+        let explanation = explain(reservations, zones, vehicles, solution, *res_id);
+        let cars: Vec<String> = explanation.vehicles.iter().map(|veh| veh.to_string()).collect();
+        let cheapest = explanation
+            .cheapest()
+            .map(|veh| format!("{} ({:+})", veh.repair, veh.repair_delta))
+            .unwrap_or_default();
+        file.write_all(
+            format!(
+                "<tr><td>req{}</td><td>z{}</td><td>{} - {}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                res.id,
+                res.zone,
+                clock(res.start),
+                clock(res.start + res.restime),
+                res.p1,
+                cars.join("<br>"),
+                cheapest
+            )
+            .as_bytes(),
+        )?;
+    }
+    file.write_all(b"</table>\n</body></html>\n")?;
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use crate::ls::{interfere, LocalSearch};
+
+impl LocalSearch {
+    // move penalty of the baseline for res ending up on veh (None: unassigned)
+    pub(crate) fn baseline_move(&self, res: i32, veh: Option<usize>) -> i32 {
+        match (&self.baseline, self.baseline_vehicle.get(res as usize).copied().flatten()) {
+            (Some(baseline), Some(base)) if Some(base as usize) != veh => baseline.move_penalty,
+            _ => 0,
+        }
+    }
+
+    // cost saved by serving res with veh where it is parked instead of leaving it unassigned
+    pub(crate) fn gain(&self, res: i32, veh: usize) -> i32 {
+        self.reservations[res as usize].p1 + self.baseline_move(res, None)
+            - self.calculate_cost(res, veh)
+            - self.baseline_move(res, Some(veh))
+    }
+
+    // car a reservation may not leave: pinned, or frozen on its baseline car
+    pub(crate) fn tied_to(&self, res: i32) -> Option<usize> {
+        if let Some((_, veh)) = self.constraints.pinned.iter().find(|(r, _)| *r == res) {
+            return Some(*veh as usize);
+        }
+        match &self.baseline {
+            Some(baseline) if baseline.frozen.contains(&res) => {
+                self.baseline_vehicle.get(res as usize).copied().flatten().map(|veh| veh as usize)
+            }
+            _ => None,
+        }
+    }
+
+    // parking veh in zone would put a reservation tied to it out of reach
+    pub(crate) fn strands_tied(&self, veh: usize, zone: i32) -> bool {
+        let frozen = self.baseline.iter().flat_map(|baseline| baseline.frozen.iter());
+        self.constraints
+            .pinned
+            .iter()
+            .map(|(res, _)| res)
+            .chain(frozen)
+            .filter(|res| self.tied_to(**res) == Some(veh))
+            .any(|res| {
+                let zon_res = self.reservations[*res as usize].zone;
+                zon_res != zone && !self.zones[zone as usize].neighbours.contains(&zon_res)
+            })
+    }
+
+    pub(crate) fn can_serve(&self, veh: usize, res: i32) -> bool {
+        let reservation = &self.reservations[res as usize];
+        let zone = self.veh_to_zon[veh];
+        (zone == reservation.zone || self.zones[zone as usize].neighbours.contains(&reservation.zone))
+            && self.vehicle_possible(veh, res as usize)
+    }
+
+    // the best set of reservations for veh where it is parked, out of its own and the unassigned
+    // ones, by weighted interval scheduling; the other cars keep theirs. true if the cost went down
+    pub fn schedule_car(&mut self, veh: usize) -> bool {
+        let own = self.veh_to_res[veh].clone();
+        let pool: Vec<i32> = own.iter().chain(self.unassigned.iter()).copied().collect();
+
+        // a tied reservation out of reach is left unassigned, which check_all rejects
+        let fixed: Vec<i32> = pool
+            .iter()
+            .copied()
+            .filter(|res| self.tied_to(*res) == Some(veh) && self.can_serve(veh, *res))
+            .collect();
+        let mut candidates: Vec<(i32, i32)> = pool
+            .iter()
+            .copied()
+            .filter(|res| {
+                self.tied_to(*res).is_none()
+                    && self.can_serve(veh, *res)
+                    && !fixed
+                        .iter()
+                        .any(|f| interfere(&self.reservations[*f as usize], &self.reservations[*res as usize]))
+            })
+            .map(|res| (res, self.gain(res, veh)))
+            .filter(|(_, gain)| *gain > 0)
+            .collect();
+        candidates.sort_by_key(|(res, _)| {
+            let res = &self.reservations[*res as usize];
+            res.start + res.restime
+        });
+
+        // best[i]: highest gain out of the first i candidates; touching reservations interfere, so
+        // the previous compatible one has to end strictly before the start
+        let ends: Vec<i32> = candidates
+            .iter()
+            .map(|(res, _)| self.reservations[*res as usize].start + self.reservations[*res as usize].restime)
+            .collect();
+        let previous: Vec<usize> = candidates
+            .iter()
+            .map(|(res, _)| {
+                let start = self.reservations[*res as usize].start;
+                ends.partition_point(|end| *end < start)
+            })
+            .collect();
+        let mut best = vec![0; candidates.len() + 1];
+        for i in 0..candidates.len() {
+            best[i + 1] = best[i].max(candidates[i].1 + best[previous[i]]);
+        }
+
+        let current: i32 = own
+            .iter()
+            .filter(|res| !fixed.contains(res))
+            .map(|res| self.gain(*res, veh))
+            .sum();
+        if best[candidates.len()] <= current {
+            return false;
+        }
+
+        let mut chosen = fixed;
+        let mut i = candidates.len();
+        while i > 0 {
+            if best[i] == best[i - 1] {
+                i -= 1;
+            } else {
+                chosen.push(candidates[i - 1].0);
+                i = previous[i - 1];
+            }
+        }
+
+        self.unassigned.extend(own);
+        self.unassigned.retain(|res| !chosen.contains(res));
+        self.veh_to_res[veh] = chosen;
+        true
+    }
+
+    // schedule every car in turn until none improves, each step is exact for its car
+    pub fn schedule_all(&mut self) -> bool {
+        let mut changed = false;
+        loop {
+            let mut round = false;
+            for veh in 0..self.vehicle.len() {
+                round |= self.schedule_car(veh);
+                self.paranoid_check(|| format!("schedule_car(car{})", veh));
+            }
+            if !round {
+                return changed;
+            }
+            changed = true;
+        }
+    }
+}
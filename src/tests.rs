@@ -0,0 +1,166 @@
+use crate::config::Config;
+use crate::data_structs::{Reservation, Vehicle, Zone};
+use crate::ls::LocalSearch;
+use crate::parser;
+use crate::validator::validate;
+use proptest::prelude::*;
+
+// the best solution is accepted by the validator at the cost the search reports
+fn assert_valid(ls: &LocalSearch) {
+    let solution = ls.best_solution();
+    assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution), Ok(solution.cost));
+    ls.check_invariants().unwrap();
+}
+
+fn solve(instance: &str, seed: u64, iterations: u64, config: Config) -> LocalSearch {
+    let (res, zone, veh) = parser::read_file(instance);
+    let mut ls = LocalSearch::new(res, zone, veh);
+    ls.set_config(Config {
+        max_iterations: Some(iterations),
+        ..config
+    });
+    ls.solve(3600, seed);
+    ls
+}
+
+// zones on a line, each a neighbour of the next; reservations as (zone, day, start, restime,
+// possible vehicles, p1, p2 as a share of p1)
+fn instance() -> impl Strategy<Value = (Vec<Reservation>, Vec<Zone>, Vec<Vehicle>)> {
+    (1..6i32, 1..8i32)
+        .prop_flat_map(|(zones, vehicles)| {
+            let reservation = (
+                0..zones,
+                0..3i32,
+                0..1440i32,
+                1..600i32,
+                prop::collection::vec(0..vehicles, 1..4),
+                50..200i32,
+                0..100i32,
+            );
+            (Just(zones), Just(vehicles), prop::collection::vec(reservation, 0..40))
+        })
+        .prop_map(|(zones, vehicles, reservations)| {
+            let reservations = reservations
+                .into_iter()
+                .enumerate()
+                .map(|(id, (zone, day, start, restime, mut possible_vehicles, p1, share))| {
+                    possible_vehicles.sort();
+                    possible_vehicles.dedup();
+                    Reservation {
+                        id: id as i32,
+                        zone,
+                        day,
+                        start: start + 1440 * day,
+                        restime,
+                        possible_vehicles,
+                        categories: vec![],
+                        p1,
+                        p2: p1 * share / 100,
+                        priority: 1,
+                        vehicle: None,
+                    }
+                })
+                .collect();
+            let zones = (0..zones)
+                .map(|id| Zone {
+                    id,
+                    neighbours: (0..zones).filter(|other| (other - id).abs() == 1).collect(),
+                    capacity: None,
+                })
+                .collect();
+            let vehicles = (0..vehicles)
+                .map(|id| Vehicle {
+                    id,
+                    zone: None,
+                    availability: vec![],
+                    category: None,
+                })
+                .collect();
+            (reservations, zones, vehicles)
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn every_constructor_builds_a_valid_solution((res, zones, vehicles) in instance(), seed in 0..100u64) {
+        for constructor in ["greedy", "demand", "regret", "grasp", "matching"] {
+            let mut ls = LocalSearch::new(res.clone(), zones.clone(), vehicles.clone());
+            ls.paranoid = true;
+            ls.config.constructor = constructor.to_string();
+            ls.construct(seed);
+            let solution = ls.current_solution();
+            prop_assert_eq!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution), Ok(solution.cost));
+        }
+    }
+
+    #[test]
+    fn searches_end_with_a_valid_solution((res, zones, vehicles) in instance(), seed in 0..100u64) {
+        for (strategy, polish, crossover) in [
+            ("threshold", "optimise", "uniform"),
+            ("threshold", "schedule", "uniform"),
+            ("flow", "optimise", "uniform"),
+            ("memetic", "optimise", "uniform"),
+            ("memetic", "schedule", "block"),
+            ("ils", "optimise", "uniform"),
+        ] {
+            let mut ls = LocalSearch::new(res.clone(), zones.clone(), vehicles.clone());
+            ls.paranoid = true;
+            ls.set_config(Config {
+                strategy: strategy.to_string(),
+                polish: polish.to_string(),
+                crossover: crossover.to_string(),
+                elite_size: 3,
+                max_iterations: Some(200),
+                ..Config::default()
+            });
+            ls.solve(3600, seed);
+            assert_valid(&ls);
+        }
+    }
+
+    #[test]
+    fn polishing_never_increases_cost((res, zones, vehicles) in instance(), seed in 0..100u64) {
+        let mut ls = LocalSearch::new(res, zones, vehicles);
+        ls.set_config(Config {
+            constructor: "grasp".to_string(),
+            grasp_alpha: 1.0,
+            ..Config::default()
+        });
+        ls.construct(seed);
+        let before = ls.calculate_full_cost();
+        ls.schedule_all();
+        let scheduled = ls.calculate_full_cost();
+        prop_assert!(scheduled <= before);
+        ls.optimise();
+        prop_assert!(ls.calculate_full_cost() <= scheduled);
+        ls.check_invariants().unwrap();
+    }
+}
+
+// costs reached with fixed seeds and iteration budgets; a change here changes search behaviour
+#[test]
+fn golden_threshold_search() {
+    for (instance, seed, iterations, cost) in [
+        ("input/toy1.csv", 1, 2000, 120),
+        ("input/100_5_14_25.csv", 7, 5000, 10420),
+        ("input/360_5_71_25.csv", 3, 3000, 12840),
+    ] {
+        let ls = solve(instance, seed, iterations, Config::default());
+        assert_valid(&ls);
+        assert_eq!(ls.best_solution().cost, cost, "{}", instance);
+    }
+}
+
+#[test]
+fn golden_flow_search() {
+    let config = Config {
+        strategy: "flow".to_string(),
+        constructor: "regret".to_string(),
+        ..Config::default()
+    };
+    let ls = solve("input/100_5_14_25.csv", 7, 200, config);
+    assert_valid(&ls);
+    assert_eq!(ls.best_solution().cost, 10350);
+}
@@ -0,0 +1,326 @@
+use crate::bench::{instances, run_instance};
+use crate::config::{write_config, Config};
+use rand::Rng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+// a tunable of the config and the values it is sampled from: an inclusive integer or float range, or
+// a list of choices for the string parameters
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Range {
+    Integer(i64, i64),
+    Float(f64, f64),
+    Choice(Vec<String>),
+}
+
+pub type Ranges = BTreeMap<String, Range>;
+
+pub fn default_ranges() -> Ranges {
+    let mut ranges = Ranges::new();
+    ranges.insert("moves_per_iteration".to_string(), Range::Integer(1, 8));
+    ranges.insert("stagnation_limit".to_string(), Range::Integer(2, 40));
+    ranges.insert("threshold_step".to_string(), Range::Integer(5, 100));
+    ranges
+}
+
+// {"stagnation_limit": [2, 40], "grasp_alpha": [0.1, 0.9], "constructor": ["greedy", "regret"]} as
+// JSON, or the same as TOML; every range must give values its parameter of the config accepts
+pub fn read_ranges(filepath: &str) -> Result<Ranges, String> {
+    let contents = fs::read_to_string(filepath).map_err(|e| format!("could not read {}: {}", filepath, e))?;
+    let mut ranges: Ranges = if filepath.ends_with(".toml") {
+        toml::from_str(&contents).map_err(|e| format!("could not parse ranges: {}", e))?
+    } else {
+        serde_json::from_str(&contents).map_err(|e| format!("could not parse ranges: {}", e))?
+    };
+
+    let config = serde_json::to_value(Config::default()).unwrap();
+    for (key, range) in ranges.iter_mut() {
+        match config.get(key.as_str()) {
+            None => return Err(format!("{} is not a parameter of the config", key)),
+            // [0, 1] reads as integers, a float parameter samples between them
+            Some(value) if value.is_f64() => {
+                if let Range::Integer(min, max) = range {
+                    *range = Range::Float(*min as f64, *max as f64);
+                }
+            }
+            Some(_) => {}
+        }
+        let ends: Vec<Value> = match &*range {
+            Range::Integer(min, max) if min <= max => vec![(*min).into(), (*max).into()],
+            Range::Float(min, max) if min <= max => vec![(*min).into(), (*max).into()],
+            Range::Choice(choices) if !choices.is_empty() => choices.iter().map(|c| c.as_str().into()).collect(),
+            _ => return Err(format!("range of {} is empty", key)),
+        };
+        for value in ends {
+            let mut values = BTreeMap::new();
+            values.insert(key.clone(), value.clone());
+            if try_values(&Config::default(), &values).is_err() {
+                return Err(format!("{} can not be {}", key, value));
+            }
+        }
+    }
+    Ok(ranges)
+}
+
+fn try_values(base: &Config, values: &BTreeMap<String, Value>) -> serde_json::Result<Config> {
+    let mut config = serde_json::to_value(base).unwrap();
+    for (key, value) in values {
+        config[key] = value.clone();
+    }
+    serde_json::from_value(config)
+}
+
+fn with_values(base: &Config, values: &BTreeMap<String, Value>) -> Config {
+    try_values(base, values).expect("range of a parameter the config does not have")
+}
+
+fn values_of(config: &Config, ranges: &Ranges) -> BTreeMap<String, Value> {
+    let config = serde_json::to_value(config).unwrap();
+    ranges.keys().map(|key| (key.clone(), config[key].clone())).collect()
+}
+
+// uniform over the ranges, or around an elite with a spread that shrinks every iteration; a choice
+// keeps the one of the elite, less often changed the later the iteration
+fn sample(base: &Config, ranges: &Ranges, elite: Option<&Config>, iteration: usize, r: &mut ChaCha12Rng) -> Config {
+    let mut values = BTreeMap::new();
+    let centre = elite.map(|elite| values_of(elite, ranges));
+    let shrink = 2.0 * (iteration + 1) as f64;
+    for (key, range) in ranges {
+        let centre = centre.as_ref().map(|centre| &centre[key]);
+        // a base config may leave an optional parameter unset, its neighbourhood is then the whole range
+        let value: Value = match range {
+            Range::Integer(min, max) => match centre.and_then(Value::as_i64) {
+                Some(centre) => {
+                    let spread = ((max - min) as f64 / shrink).ceil() as i64;
+                    (centre + r.gen_range(-spread..=spread)).clamp(*min, *max).into()
+                }
+                None => r.gen_range(*min..=*max).into(),
+            },
+            Range::Float(min, max) => match centre.and_then(Value::as_f64) {
+                Some(centre) => {
+                    let spread = (max - min) / shrink;
+                    (centre + r.gen_range(-spread..=spread)).clamp(*min, *max).into()
+                }
+                None => r.gen_range(*min..=*max).into(),
+            },
+            Range::Choice(choices) => match centre {
+                Some(centre) if centre.is_string() && !r.gen_bool(1.0 / shrink) => centre.clone(),
+                _ => choices[r.gen_range(0..choices.len())].as_str().into(),
+            },
+        };
+        values.insert(key.clone(), value);
+    }
+    with_values(base, &values)
+}
+
+// ranks within one block, ties get the average rank
+fn ranks(costs: &[i32]) -> Vec<f64> {
+    costs
+        .iter()
+        .map(|c| {
+            let below = costs.iter().filter(|o| *o < c).count() as f64;
+            let equal = costs.iter().filter(|o| *o == c).count() as f64;
+            below + (equal + 1.0) / 2.0
+        })
+        .collect()
+}
+
+// Friedman test over the blocks raced so far; the candidates (by index into `results`) whose rank
+// sum is significantly worse than the best one
+fn eliminated(results: &[Vec<i32>]) -> Vec<usize> {
+    let k = results.len() as f64;
+    let b = results[0].len() as f64;
+    let mut rank_sums = vec![0.0; results.len()];
+    for block in 0..results[0].len() {
+        let costs: Vec<i32> = results.iter().map(|r| r[block]).collect();
+        for (sum, rank) in rank_sums.iter_mut().zip(ranks(&costs)) {
+            *sum += rank;
+        }
+    }
+
+    let statistic = 12.0 / (b * k * (k + 1.0)) * rank_sums.iter().map(|r| r * r).sum::<f64>() - 3.0 * b * (k + 1.0);
+    // chi-square quantile at 0.95 with k-1 degrees of freedom (Wilson-Hilferty)
+    let df = k - 1.0;
+    let critical = df * (1.0 - 2.0 / (9.0 * df) + 1.645 * (2.0 / (9.0 * df)).sqrt()).powi(3);
+    if statistic <= critical {
+        return vec![];
+    }
+
+    let best = rank_sums.iter().cloned().fold(f64::MAX, f64::min);
+    let difference = 1.96 * (b * k * (k + 1.0) / 6.0).sqrt();
+    (0..results.len()).filter(|c| rank_sums[*c] - best > difference).collect()
+}
+
+pub struct Tuner {
+    pub instances: Vec<String>,
+    pub time: i32,
+    pub budget: usize, // number of solver runs
+    pub base: Config,
+    pub ranges: Ranges,
+    pub first_test: usize, // blocks raced before the first elimination
+    cache: HashMap<(String, usize), i32>,
+    spent: usize,
+}
+
+impl Tuner {
+    pub fn new(dir: &str, time: i32, budget: usize, base: Config, ranges: Ranges) -> Tuner {
+        Tuner {
+            instances: instances(dir),
+            time,
+            budget,
+            base,
+            ranges,
+            first_test: 3,
+            cache: HashMap::new(),
+            spent: 0,
+        }
+    }
+
+    // block b is an instance and a seed; every iteration races on the same sequence of blocks
+    fn cost(&mut self, config: &Config, block: usize) -> i32 {
+        let key = (config.to_json(), block);
+        if let Some(cost) = self.cache.get(&key) {
+            return *cost;
+        }
+        let instance = &self.instances[block % self.instances.len()];
+        let seed = (block / self.instances.len()) as u64;
+        let run = run_instance(instance, self.time, seed, config);
+        let cost = if run.valid.is_ok() { run.cost } else { i32::MAX };
+        self.spent += 1;
+        self.cache.insert(key, cost);
+        cost
+    }
+
+    // the survivors, best mean rank first
+    fn race(&mut self, candidates: Vec<Config>, budget: usize) -> Vec<Config> {
+        let mut raced: Vec<(Config, Vec<i32>)> = candidates.into_iter().map(|c| (c, vec![])).collect();
+        let stop = (self.spent + budget).min(self.budget);
+        let mut block = 0;
+        while raced.len() > 1 && self.spent + raced.len() <= stop {
+            for (config, results) in raced.iter_mut() {
+                results.push(self.cost(config, block));
+            }
+            block += 1;
+            if block >= self.first_test {
+                let results: Vec<Vec<i32>> = raced.iter().map(|(_, r)| r.clone()).collect();
+                let out = eliminated(&results);
+                raced = raced
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(c, _)| !out.contains(c))
+                    .map(|(_, raced)| raced)
+                    .collect();
+            }
+        }
+
+        let mut rank_sums = vec![0.0; raced.len()];
+        for b in 0..block {
+            let costs: Vec<i32> = raced.iter().map(|(_, r)| r[b]).collect();
+            for (sum, rank) in rank_sums.iter_mut().zip(ranks(&costs)) {
+                *sum += rank;
+            }
+        }
+        let mut order: Vec<(f64, Config)> = rank_sums.into_iter().zip(raced.into_iter().map(|(c, _)| c)).collect();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0));
+        order.into_iter().map(|(_, config)| config).collect()
+    }
+
+    pub fn tune(&mut self, seed: u64) -> Config {
+        let mut r = ChaCha12Rng::seed_from_u64(seed);
+        let iterations = 2 + (self.ranges.len() as f64).log2().ceil() as usize;
+        let mut elites: Vec<Config> = vec![self.base.clone()];
+
+        for iteration in 0..iterations {
+            if self.spent >= self.budget {
+                break;
+            }
+            let budget = (self.budget - self.spent) / (iterations - iteration);
+            let size = (budget / (self.first_test + iteration.min(5))).max(2);
+
+            let mut candidates = elites.clone();
+            // a few tries per slot, small ranges may not hold `size` different configs
+            for _ in 0..size * 10 {
+                if candidates.len() >= size {
+                    break;
+                }
+                let elite = if iteration == 0 { None } else { Some(&elites[r.gen_range(0..elites.len())]) };
+                let candidate = sample(&self.base, &self.ranges, elite, iteration, &mut r);
+                if !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+
+            let survivors = self.race(candidates, budget);
+            elites = survivors.into_iter().take(2 + self.ranges.len() / 2).collect();
+            println!(
+                "iteration {}: {} runs spent, best {}",
+                iteration + 1,
+                self.spent,
+                serde_json::to_string(&values_of(&elites[0], &self.ranges)).unwrap()
+            );
+        }
+        elites.swap_remove(0)
+    }
+}
+
+pub fn tune(dir: &str, output: &str, budget: usize, time: i32, base: Config, ranges: Ranges, seed: u64) -> std::io::Result<Config> {
+    let mut tuner = Tuner::new(dir, time, budget, base, ranges);
+    if tuner.instances.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("no instances in {}", dir)));
+    }
+    let best = tuner.tune(seed);
+    write_config(output, &best)?;
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges_from(name: &str, contents: &str) -> Result<Ranges, String> {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        read_ranges(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn float_and_categorical_ranges_are_sampled() {
+        let ranges = ranges_from(
+            "tune_ranges.json",
+            r#"{"grasp_alpha": [0, 1], "mutation_rate": [0.05, 0.5], "constructor": ["greedy", "regret"], "stagnation_limit": [2, 40]}"#,
+        )
+        .unwrap();
+        assert_eq!(ranges["grasp_alpha"], Range::Float(0.0, 1.0));
+
+        let mut r = ChaCha12Rng::seed_from_u64(0);
+        let base = Config::default();
+        let mut elite = sample(&base, &ranges, None, 0, &mut r);
+        for iteration in 0..20 {
+            let config = sample(&base, &ranges, Some(&elite), iteration, &mut r);
+            assert!((0.0..=1.0).contains(&config.grasp_alpha));
+            assert!((0.05..=0.5).contains(&config.mutation_rate));
+            assert!(["greedy", "regret"].contains(&config.constructor.as_str()));
+            assert!((2..=40).contains(&config.stagnation_limit));
+            elite = config;
+        }
+    }
+
+    #[test]
+    fn unusable_ranges_and_directories_are_rejected() {
+        assert!(ranges_from("tune_unknown.toml", "speed = [1, 2]").is_err());
+        assert!(ranges_from("tune_float.toml", "stagnation_limit = [1.5, 4.5]").is_err());
+        assert!(ranges_from("tune_bool.toml", "exact_repair = [\"yes\"]").is_err());
+        assert!(ranges_from("tune_empty.toml", "stagnation_limit = [40, 2]").is_err());
+
+        let dir = std::env::temp_dir().join("tune_no_instances");
+        fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("best.json");
+        let result = tune(dir.to_str().unwrap(), output.to_str().unwrap(), 10, 1, Config::default(), default_ranges(), 0);
+        assert!(result.is_err());
+    }
+}
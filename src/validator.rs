@@ -0,0 +1,128 @@
+use crate::data_structs::{Constraints, Reservation, Solution, Vehicle, Zone};
+
+// independent of LocalSearch: only uses the instance and the written solution. Intervals are
+// closed, as in the search: one reservation ending when the next starts still overlaps it
+fn overlaps(res_1: &Reservation, res_2: &Reservation) -> bool {
+    res_1.start <= res_2.start + res_2.restime && res_2.start <= res_1.start + res_1.restime
+}
+
+fn reservation(reservations: &[Reservation], res_id: i32) -> Result<&Reservation, String> {
+    usize::try_from(res_id)
+        .ok()
+        .and_then(|res| reservations.get(res))
+        .ok_or(format!("req{} does not exist", res_id))
+}
+
+pub fn validate(reservations: &[Reservation], zones: &[Zone], vehicles: &[Vehicle], solution: &Solution) -> Result<i32, String> {
+    if solution.veh_to_zon.len() != vehicles.len() || solution.veh_to_res.len() != vehicles.len() {
+        return Err(format!(
+            "{} zones and {} reservation lists for {} cars",
+            solution.veh_to_zon.len(),
+            solution.veh_to_res.len(),
+            vehicles.len()
+        ));
+    }
+    let mut seen = vec![0; reservations.len()];
+    let mut cost = 0;
+
+    for (veh, assigned) in solution.veh_to_res.iter().enumerate() {
+        let zon_veh = solution.veh_to_zon[veh];
+        let zone = usize::try_from(zon_veh)
+            .ok()
+            .and_then(|zone| zones.get(zone))
+            .ok_or(format!("car{} is parked in z{}, which does not exist", veh, zon_veh))?;
+        for res_id in assigned {
+            let res = reservation(reservations, *res_id)?;
+            seen[*res_id as usize] += 1;
+
+            if !res.allows(&vehicles[veh]) {
+                return Err(format!("req{} can not be served by car{}", res_id, veh));
+            }
+            if !vehicles[veh].available(res) {
+                return Err(format!("car{} is not available during req{}", veh, res_id));
+            }
+            cost += res.upgrade_penalty(&vehicles[veh]);
+            if res.zone == zon_veh {
+                // own zone is free
+            } else if zone.neighbours.contains(&res.zone) {
+                cost += res.p2;
+            } else {
+                return Err(format!("req{} (z{}) is not reachable from car{} (z{})", res_id, res.zone, veh, zon_veh));
+            }
+
+            for other in assigned {
+                if other != res_id && overlaps(res, reservation(reservations, *other)?) {
+                    return Err(format!("req{} and req{} overlap on car{}", res_id, other, veh));
+                }
+            }
+        }
+    }
+
+    for res_id in &solution.unassigned {
+        let res = reservation(reservations, *res_id)?;
+        seen[*res_id as usize] += 1;
+        cost += res.p1;
+    }
+
+    if let Some(res_id) = seen.iter().position(|x| *x != 1) {
+        return Err(format!("req{} appears {} times", res_id, seen[res_id]));
+    }
+    if cost != solution.cost {
+        return Err(format!("reported cost {} but calculated {}", solution.cost, cost));
+    }
+    Ok(cost)
+}
+
+pub fn validate_constraints(reservations: &[Reservation], solution: &Solution, constraints: &Constraints) -> Result<(), String> {
+    constraints.check(reservations, &solution.veh_to_res, &solution.veh_to_zon)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ls::LocalSearch;
+    use crate::parser;
+
+    fn constructed() -> (LocalSearch, Solution) {
+        let (res, zone, veh) = parser::read_file("input/toy1.csv");
+        let mut ls = LocalSearch::new(res, zone, veh);
+        ls.construct(0);
+        let solution = ls.current_solution();
+        (ls, solution)
+    }
+
+    #[test]
+    fn touching_reservations_on_one_car_are_rejected() {
+        let (mut ls, _) = constructed();
+        // req9 is moved to end exactly when req7 starts, both in z4 and allowed on car3
+        ls.reservations[9].start = 568 - ls.reservations[9].restime;
+        ls.veh_to_zon = vec![4, 0, 0, 4, 0, 0];
+        ls.veh_to_res = vec![vec![7], vec![], vec![], vec![9], vec![], vec![]];
+        ls.unassigned = vec![0, 1, 2, 3, 4, 5, 6, 8];
+        let mut solution = ls.current_solution();
+        assert!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution).is_ok());
+
+        ls.veh_to_res = vec![vec![], vec![], vec![], vec![7, 9], vec![], vec![]];
+        solution = ls.current_solution();
+        assert!(validate(&ls.reservations, &ls.zones, &ls.vehicle, &solution).is_err());
+    }
+
+    #[test]
+    fn indices_out_of_range_are_errors() {
+        let (ls, solution) = constructed();
+        let check = |solution: &Solution| validate(&ls.reservations, &ls.zones, &ls.vehicle, solution);
+
+        let mut zone = solution.clone();
+        zone.veh_to_zon[0] = ls.zones.len() as i32;
+        assert!(check(&zone).is_err());
+
+        let mut cars = solution.clone();
+        cars.veh_to_zon.push(0);
+        cars.veh_to_res.push(vec![]);
+        assert!(check(&cars).is_err());
+
+        let mut res = solution.clone();
+        res.veh_to_res[0].push(-1);
+        assert!(check(&res).is_err());
+    }
+}